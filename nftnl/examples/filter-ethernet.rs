@@ -22,13 +22,16 @@
 //! # nft delete table inet example-filter-ethernet
 //! ```
 
-use nftnl::{nft_expr, nftnl_sys::libc, Batch, Chain, FinalizedBatch, ProtoFamily, Rule, Table};
+use nftnl::{
+    nft_expr, nftnl_sys::libc, types::MacAddr, Batch, Chain, FinalizedBatch, ProtoFamily, Rule,
+    Table,
+};
 use std::{ffi::CString, io};
 
 const TABLE_NAME: &str = "example-filter-ethernet";
 const OUT_CHAIN_NAME: &str = "chain-for-outgoing-packets";
 
-const BLOCK_THIS_MAC: &[u8] = &[0, 0, 0, 0, 0, 0];
+const BLOCK_THIS_MAC: &str = "00:00:00:00:00:00";
 
 fn main() -> io::Result<()> {
     // For verbose explanations of what all these lines up until the rule creation does, see the
@@ -53,7 +56,8 @@ fn main() -> io::Result<()> {
 
     // Compare the ethernet destination address against the MAC address we want to drop
     block_ethernet_rule.add_expr(&nft_expr!(payload ethernet daddr));
-    block_ethernet_rule.add_expr(&nft_expr!(cmp == BLOCK_THIS_MAC));
+    let mac: MacAddr = BLOCK_THIS_MAC.parse().unwrap();
+    block_ethernet_rule.add_expr(&nft_expr!(cmp == mac));
 
     // Drop the matching packets.
     block_ethernet_rule.add_expr(&nft_expr!(verdict drop));
@@ -1,7 +1,12 @@
 use crate::{MsgType, NlMsg};
 use core::fmt;
 use nftnl_sys::{self as sys, libc};
+#[cfg(feature = "mnl")]
+use std::collections::HashSet;
 use std::ffi::c_void;
+#[cfg(feature = "mnl")]
+use std::io;
+use std::mem;
 use std::os::raw::c_char;
 use std::ptr;
 
@@ -17,6 +22,21 @@ impl fmt::Display for NetlinkError {
 
 impl std::error::Error for NetlinkError {}
 
+/// Error returned by [`Batch::write_raw_message`] when the given bytes are not a single valid
+/// netlink message.
+///
+/// [`Batch::write_raw_message`]: struct.Batch.html#method.write_raw_message
+#[derive(Debug)]
+pub struct BatchError(());
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "Not a single valid netlink message".fmt(f)
+    }
+}
+
+impl std::error::Error for BatchError {}
+
 /// Check if the kernel supports batched netlink messages to netfilter.
 pub fn batch_is_supported() -> std::result::Result<bool, NetlinkError> {
     match unsafe { sys::nftnl_batch_is_supported() } {
@@ -28,6 +48,22 @@ pub fn batch_is_supported() -> std::result::Result<bool, NetlinkError> {
 
 /// A batch of netfilter messages to be performed in one atomic operation. Corresponds to
 /// `nftnl_batch` in libnftnl.
+///
+/// # Message order
+///
+/// The kernel processes the messages in a batch in the order they were added, and an object must
+/// exist before anything referring to it is created. So a [`Table`] must be added before the
+/// [`Chain`]s in it, a chain before the [`Rule`]s in it, and a set before its elements and the
+/// rules looking things up in it. The messages are written directly into the underlying
+/// `nftnl_batch` and can't be reordered afterwards, so the order of the calls to [`add`] must
+/// follow these dependencies. Objects that already exist in the kernel can of course be referred
+/// to without being part of the batch. Deleting objects has the opposite requirement: rules must
+/// be deleted before the chain holding them and so on.
+///
+/// [`Table`]: struct.Table.html
+/// [`Chain`]: struct.Chain.html
+/// [`Rule`]: struct.Rule.html
+/// [`add`]: #method.add
 pub struct Batch {
     batch: *mut sys::nftnl_batch,
     seq: u32,
@@ -62,13 +98,68 @@ impl Batch {
         this
     }
 
-    /// Adds the given message to this batch.
+    /// Adds the given message to this batch. See [the type level documentation] for the order
+    /// messages must be added in.
+    ///
+    /// The message always requests an acknowledgement with `NLM_F_ACK`, so
+    /// [`FinalizedBatch::send_and_await_acks`] can tell whether the batch was applied.
+    ///
+    /// [the type level documentation]: #message-order
+    /// [`FinalizedBatch::send_and_await_acks`]: struct.FinalizedBatch.html#method.send_and_await_acks
+    ///
+    /// # Panics
+    ///
+    /// Panics if `msg` can not be written with `msg_type`, for example when replacing anything but
+    /// a [`Rule`]. The batch is left unchanged in that case.
+    ///
+    /// [`Rule`]: struct.Rule.html
     pub fn add<T: NlMsg>(&mut self, msg: &T, msg_type: MsgType) {
+        assert!(
+            msg.supports_msg_type(msg_type),
+            "Message can not be written with MsgType::{:?}",
+            msg_type
+        );
         trace!("Writing NlMsg with seq {} to batch", self.seq);
-        unsafe { msg.write(self.current(), self.seq, msg_type) };
+        unsafe {
+            msg.write(self.current(), self.seq, msg_type);
+            self.request_ack();
+        }
         self.next()
     }
 
+    /// Adds a netlink message that was serialized elsewhere, for example by another netlink
+    /// library, to this batch. The sequence number in the message header is overwritten with the
+    /// one of this batch, and `NLM_F_ACK` is set like for messages added with [`add`].
+    ///
+    /// `msg` must be exactly one netlink message, so its length must match the `nlmsg_len` field
+    /// of the header, be a multiple of four bytes and be at most [`nft_nlmsg_maxsize`]. The
+    /// content after the header is not checked.
+    ///
+    /// [`add`]: #method.add
+    /// [`nft_nlmsg_maxsize`]: fn.nft_nlmsg_maxsize.html
+    pub fn write_raw_message(&mut self, msg: &[u8]) -> Result<(), BatchError> {
+        if msg.len() < mem::size_of::<libc::nlmsghdr>()
+            || msg.len() > crate::nft_nlmsg_maxsize() as usize
+            || msg.len() % 4 != 0
+        {
+            return Err(BatchError(()));
+        }
+        let header = unsafe { ptr::read_unaligned(msg.as_ptr() as *const libc::nlmsghdr) };
+        if header.nlmsg_len as usize != msg.len() {
+            return Err(BatchError(()));
+        }
+
+        trace!("Writing raw message with seq {} to batch", self.seq);
+        unsafe {
+            let buf = self.current();
+            ptr::copy_nonoverlapping(msg.as_ptr(), buf as *mut u8, msg.len());
+            (*(buf as *mut libc::nlmsghdr)).nlmsg_seq = self.seq;
+            self.request_ack();
+        }
+        self.next();
+        Ok(())
+    }
+
     /// Adds all the messages in the given iterator to this batch. If any message fails to be added
     /// the error for that failure is returned and all messages up until that message stays added
     /// to the batch.
@@ -95,6 +186,12 @@ impl Batch {
         unsafe { sys::nftnl_batch_buffer(self.batch) }
     }
 
+    /// Sets `NLM_F_ACK` on the message just written at the current position. Without it the
+    /// kernel does not reply to successful messages, and there would be nothing to wait for.
+    unsafe fn request_ack(&mut self) {
+        (*(self.current() as *mut libc::nlmsghdr)).nlmsg_flags |= libc::NLM_F_ACK as u16;
+    }
+
     fn next(&mut self) {
         if unsafe { sys::nftnl_batch_update(self.batch) } < 0 {
             // See try_alloc definition.
@@ -158,6 +255,54 @@ impl FinalizedBatch {
     }
 }
 
+#[cfg(feature = "mnl")]
+impl FinalizedBatch {
+    /// Sends all messages in this batch to netfilter over `socket`. Does not wait for the
+    /// replies, see [`send_and_await_acks`] for that.
+    ///
+    /// [`send_and_await_acks`]: #method.send_and_await_acks
+    pub fn send_to_socket(&self, socket: &mnl::Socket) -> io::Result<()> {
+        socket.send_all(self)
+    }
+
+    /// Sends all messages in this batch to netfilter over `socket` and waits until the kernel
+    /// acknowledged every message. [`Batch`] makes all messages request an acknowledgement. If the kernel rejects the batch, the error it
+    /// reported for the first failing message is returned. Nothing in the batch is applied then.
+    ///
+    /// [`Batch`]: struct.Batch.html
+    pub fn send_and_await_acks(&self, socket: &mnl::Socket) -> io::Result<()> {
+        let mut pending_acks = self.acked_sequence_numbers();
+        self.send_to_socket(socket)?;
+
+        let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+        while !pending_acks.is_empty() {
+            let len = socket.recv(&mut buffer)?;
+            for header in crate::netlink::Messages::new(&buffer[..len]) {
+                if libc::c_int::from(header.nlmsg_type) != libc::NLMSG_ERROR {
+                    continue;
+                }
+                // The header is followed by the negated errno. Zero is an acknowledgement.
+                let error =
+                    unsafe { *((header as *const libc::nlmsghdr).add(1) as *const libc::c_int) };
+                if error != 0 {
+                    return Err(io::Error::from_raw_os_error(-error));
+                }
+                pending_acks.remove(&header.nlmsg_seq);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the sequence numbers of the messages in this batch that have `NLM_F_ACK` set.
+    fn acked_sequence_numbers(&self) -> HashSet<u32> {
+        self.iter()
+            .flat_map(crate::netlink::Messages::new)
+            .filter(|header| header.nlmsg_flags & libc::NLM_F_ACK as u16 != 0)
+            .map(|header| header.nlmsg_seq)
+            .collect()
+    }
+}
+
 impl<'a> IntoIterator for &'a FinalizedBatch {
     type Item = &'a [u8];
     type IntoIter = Iter<'a>;
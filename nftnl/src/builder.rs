@@ -0,0 +1,182 @@
+//! Declarative builders for describing a [`Table`] together with its [`Chain`]s and [`Rule`]s
+//! before writing them all to a [`Batch`] in one go.
+//!
+//! [`Table`]: struct.Table.html
+//! [`Chain`]: struct.Chain.html
+//! [`Rule`]: struct.Rule.html
+//! [`Batch`]: struct.Batch.html
+
+use crate::{
+    expr::Expression, Batch, Chain, ChainType, Hook, MsgType, Policy, Priority, ProtoFamily, Rule,
+    Table,
+};
+use std::ffi::{CStr, CString};
+
+/// Describes a [`Table`] and all the chains that should be created inside it.
+///
+/// ```ignore
+/// let table = TableBuilder::new(&table_name, ProtoFamily::Inet)
+///     .add_chain(
+///         ChainBuilder::new(&chain_name)
+///             .with_hook(Hook::In, 0)
+///             .with_policy(Policy::Drop)
+///             .add_rule(RuleBuilder::new().add_expr(nft_expr!(verdict accept))),
+///     )
+///     .into_table(&mut batch);
+/// ```
+///
+/// [`Table`]: struct.Table.html
+pub struct TableBuilder {
+    name: CString,
+    family: ProtoFamily,
+    chains: Vec<ChainBuilder>,
+}
+
+impl TableBuilder {
+    /// Creates a new table description with the given name and protocol family.
+    pub fn new<T: AsRef<CStr>>(name: &T, family: ProtoFamily) -> Self {
+        TableBuilder {
+            name: name.as_ref().to_owned(),
+            family,
+            chains: Vec::new(),
+        }
+    }
+
+    /// Adds a chain to this table. Chains are added to the batch in the order they are added here.
+    pub fn add_chain(mut self, chain: ChainBuilder) -> Self {
+        self.chains.push(chain);
+        self
+    }
+
+    /// Adds the table, followed by all of its chains and their rules, to `batch` with
+    /// `MsgType::Add`. Returns the created [`Table`].
+    ///
+    /// [`Table`]: struct.Table.html
+    pub fn into_table(self, batch: &mut Batch) -> Table {
+        let table = Table::new(&self.name, self.family);
+        batch.add(&table, MsgType::Add);
+        for chain in &self.chains {
+            chain.add_to_batch(&table, batch);
+        }
+        table
+    }
+}
+
+/// Describes a [`Chain`] and the rules that should be added to it. Used together with
+/// [`TableBuilder`].
+///
+/// [`Chain`]: struct.Chain.html
+/// [`TableBuilder`]: struct.TableBuilder.html
+pub struct ChainBuilder {
+    name: CString,
+    hook: Option<(Hook, Priority)>,
+    policy: Option<Policy>,
+    chain_type: Option<ChainType>,
+    device: Option<CString>,
+    rules: Vec<RuleBuilder>,
+}
+
+impl ChainBuilder {
+    /// Creates a new chain description with the given name.
+    pub fn new<T: AsRef<CStr>>(name: &T) -> Self {
+        ChainBuilder {
+            name: name.as_ref().to_owned(),
+            hook: None,
+            policy: None,
+            chain_type: None,
+            device: None,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Registers the chain with the given hook and priority. See [`Chain::set_hook`].
+    ///
+    /// [`Chain::set_hook`]: struct.Chain.html#method.set_hook
+    pub fn with_hook(mut self, hook: Hook, priority: Priority) -> Self {
+        self.hook = Some((hook, priority));
+        self
+    }
+
+    /// Sets the default policy of the chain. See [`Chain::set_policy`].
+    ///
+    /// [`Chain::set_policy`]: struct.Chain.html#method.set_policy
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Sets the base chain type. See [`Chain::set_type`].
+    ///
+    /// [`Chain::set_type`]: struct.Chain.html#method.set_type
+    pub fn with_type(mut self, chain_type: ChainType) -> Self {
+        self.chain_type = Some(chain_type);
+        self
+    }
+
+    /// Binds the chain to a network device. See [`Chain::set_device`].
+    ///
+    /// [`Chain::set_device`]: struct.Chain.html#method.set_device
+    pub fn with_device<T: AsRef<CStr>>(mut self, device: &T) -> Self {
+        self.device = Some(device.as_ref().to_owned());
+        self
+    }
+
+    /// Adds a rule to the end of this chain.
+    pub fn add_rule(mut self, rule: RuleBuilder) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    fn add_to_batch(&self, table: &Table, batch: &mut Batch) {
+        let mut chain = Chain::new(&self.name, table);
+        if let Some((hook, priority)) = self.hook {
+            chain.set_hook(hook, priority);
+        }
+        if let Some(chain_type) = self.chain_type {
+            chain.set_type(chain_type);
+        }
+        if let Some(policy) = self.policy {
+            chain.set_policy(policy);
+        }
+        if let Some(ref device) = self.device {
+            chain.set_device(device);
+        }
+        batch.add(&chain, MsgType::Add);
+
+        for rule in &self.rules {
+            rule.add_to_batch(&chain, batch);
+        }
+    }
+}
+
+/// Describes a [`Rule`] as a list of expressions. Used together with [`ChainBuilder`].
+///
+/// [`Rule`]: struct.Rule.html
+/// [`ChainBuilder`]: struct.ChainBuilder.html
+#[derive(Default)]
+pub struct RuleBuilder {
+    exprs: Vec<Box<dyn Expression>>,
+}
+
+impl RuleBuilder {
+    /// Creates a new rule description without any expressions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an expression to the rule. See [`Rule::add_expr`].
+    ///
+    /// [`Rule::add_expr`]: struct.Rule.html#method.add_expr
+    pub fn add_expr<E: Expression + 'static>(mut self, expr: E) -> Self {
+        self.exprs.push(Box::new(expr));
+        self
+    }
+
+    fn add_to_batch(&self, chain: &Chain<'_>, batch: &mut Batch) {
+        let mut rule = Rule::new(chain);
+        for expr in &self.exprs {
+            rule.add_expr(expr.as_ref());
+        }
+        batch.add(&rule, MsgType::Add);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{MsgType, Table};
+use crate::{query::ChainInfo, MsgType, Table};
 use nftnl_sys::{self as sys, libc};
 use std::{
     ffi::{c_void, CStr},
@@ -24,6 +24,22 @@ pub enum Hook {
     PostRouting = libc::NF_INET_POST_ROUTING as u16,
 }
 
+impl Hook {
+    pub(crate) fn from_raw(hook: u32) -> Option<Hook> {
+        const HOOKS: [Hook; 5] = [
+            Hook::PreRouting,
+            Hook::In,
+            Hook::Forward,
+            Hook::Out,
+            Hook::PostRouting,
+        ];
+        HOOKS
+            .iter()
+            .find(|candidate| **candidate as u32 == hook)
+            .copied()
+    }
+}
+
 /// A chain policy. Decides what to do with a packet that was processed by the chain but did not
 /// match any rules.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -35,6 +51,16 @@ pub enum Policy {
     Drop = libc::NF_DROP as u32,
 }
 
+impl Policy {
+    pub(crate) fn from_raw(policy: u32) -> Option<Policy> {
+        match policy as libc::c_int {
+            libc::NF_ACCEPT => Some(Policy::Accept),
+            libc::NF_DROP => Some(Policy::Drop),
+            _ => None,
+        }
+    }
+}
+
 /// Base chain type.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum ChainType {
@@ -57,6 +83,13 @@ impl ChainType {
             ChainType::Nat => b"nat\0",
         }
     }
+
+    pub(crate) fn from_c_str(chain_type: &CStr) -> Option<ChainType> {
+        [ChainType::Filter, ChainType::Route, ChainType::Nat]
+            .iter()
+            .find(|candidate| candidate.as_c_str() == chain_type.to_bytes_with_nul())
+            .copied()
+    }
 }
 
 /// Abstraction of a `nftnl_chain`. Chains reside inside [`Table`]s and they hold [`Rule`]s.
@@ -72,8 +105,8 @@ pub struct Chain<'a> {
     table: &'a Table,
 }
 
-// Safety: It should be safe to pass this around and *read* from it
-// from multiple threads
+// Safety: libnftnl does not synchronize access to the object. All methods mutating it take
+// `&mut self`, so only reads can happen concurrently. See "Thread safety" in the crate docs.
 unsafe impl<'a> Send for Chain<'a> {}
 unsafe impl<'a> Sync for Chain<'a> {}
 
@@ -106,6 +139,11 @@ impl<'a> Chain<'a> {
     /// By calling `set_hook` with a hook the chain that is created will be registered with that
     /// hook and is thus a "base chain". A "base chain" is an entry point for packets from the
     /// networking stack.
+    ///
+    /// Chains registered to the same hook are evaluated in order of increasing priority. See the
+    /// [`priority`] module for the standard priorities.
+    ///
+    /// [`priority`]: priority/index.html
     pub fn set_hook(&mut self, hook: Hook, priority: Priority) {
         unsafe {
             sys::nftnl_chain_set_u32(self.chain, sys::NFTNL_CHAIN_HOOKNUM as u16, hook as u32);
@@ -133,6 +171,85 @@ impl<'a> Chain<'a> {
         }
     }
 
+    /// Sets the network device this chain is bound to. Only relevant for base chains in tables of
+    /// the [`ProtoFamily::NetDev`] family, where the hook is attached to a specific interface.
+    ///
+    /// [`ProtoFamily::NetDev`]: enum.ProtoFamily.html#variant.NetDev
+    pub fn set_device<T: AsRef<CStr>>(&mut self, device: &T) {
+        unsafe {
+            sys::nftnl_chain_set_str(
+                self.chain,
+                sys::NFTNL_CHAIN_DEV as u16,
+                device.as_ref().as_ptr(),
+            );
+        }
+    }
+
+    /// Returns the network device set with [`set_device`], if any.
+    ///
+    /// [`set_device`]: #method.set_device
+    pub fn get_device(&self) -> Option<&CStr> {
+        unsafe {
+            if !sys::nftnl_chain_is_set(self.chain, sys::NFTNL_CHAIN_DEV as u16) {
+                return None;
+            }
+            let ptr = sys::nftnl_chain_get_str(self.chain, sys::NFTNL_CHAIN_DEV as u16);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the hook and priority set with [`set_hook`], or `None` for a regular chain.
+    ///
+    /// [`set_hook`]: #method.set_hook
+    pub fn get_hook(&self) -> Option<(Hook, Priority)> {
+        unsafe {
+            if !sys::nftnl_chain_is_set(self.chain, sys::NFTNL_CHAIN_HOOKNUM as u16) {
+                return None;
+            }
+            let hook = Hook::from_raw(sys::nftnl_chain_get_u32(
+                self.chain,
+                sys::NFTNL_CHAIN_HOOKNUM as u16,
+            ))?;
+            let priority = sys::nftnl_chain_get_s32(self.chain, sys::NFTNL_CHAIN_PRIO as u16);
+            Some((hook, priority))
+        }
+    }
+
+    /// Returns the policy set with [`set_policy`], if any.
+    ///
+    /// [`set_policy`]: #method.set_policy
+    pub fn get_policy(&self) -> Option<Policy> {
+        unsafe {
+            if !sys::nftnl_chain_is_set(self.chain, sys::NFTNL_CHAIN_POLICY as u16) {
+                return None;
+            }
+            Policy::from_raw(sys::nftnl_chain_get_u32(
+                self.chain,
+                sys::NFTNL_CHAIN_POLICY as u16,
+            ))
+        }
+    }
+
+    /// Returns the type set with [`set_type`], if any.
+    ///
+    /// [`set_type`]: #method.set_type
+    pub fn get_type(&self) -> Option<ChainType> {
+        unsafe {
+            if !sys::nftnl_chain_is_set(self.chain, sys::NFTNL_CHAIN_TYPE as u16) {
+                return None;
+            }
+            let ptr = sys::nftnl_chain_get_str(self.chain, sys::NFTNL_CHAIN_TYPE as u16);
+            if ptr.is_null() {
+                return None;
+            }
+            ChainType::from_c_str(CStr::from_ptr(ptr))
+        }
+    }
+
     /// Returns the name of this chain.
     pub fn get_name(&self) -> &CStr {
         unsafe {
@@ -141,6 +258,12 @@ impl<'a> Chain<'a> {
         }
     }
 
+    /// Returns the name of this chain as a `String`, replacing invalid UTF-8 with `U+FFFD`. For
+    /// logging and other places where the exact bytes don't matter.
+    pub fn get_name_string(&self) -> String {
+        self.get_name().to_string_lossy().into_owned()
+    }
+
     /// Returns a reference to the [`Table`] this chain belongs to
     ///
     /// [`Table`]: struct.Table.html
@@ -172,10 +295,20 @@ unsafe impl<'a> crate::NlMsg for Chain<'a> {
         let raw_msg_type = match msg_type {
             MsgType::Add => libc::NFT_MSG_NEWCHAIN,
             MsgType::Del => libc::NFT_MSG_DELCHAIN,
+            MsgType::Flush => {
+                return crate::rule::write_flush_msg(
+                    buf,
+                    seq,
+                    self.table.get_family(),
+                    self.table.get_name(),
+                    Some(self.get_name()),
+                );
+            }
+            MsgType::Replace => panic!("Chains can not be replaced"),
         };
         let flags: u16 = match msg_type {
             MsgType::Add => (libc::NLM_F_ACK | libc::NLM_F_CREATE) as u16,
-            MsgType::Del => libc::NLM_F_ACK as u16,
+            _ => libc::NLM_F_ACK as u16,
         };
         let header = sys::nftnl_nlmsg_build_hdr(
             buf as *mut c_char,
@@ -186,6 +319,10 @@ unsafe impl<'a> crate::NlMsg for Chain<'a> {
         );
         sys::nftnl_chain_nlmsg_build_payload(header, self.chain);
     }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        msg_type != MsgType::Replace
+    }
 }
 
 impl<'a> Drop for Chain<'a> {
@@ -193,3 +330,45 @@ impl<'a> Drop for Chain<'a> {
         unsafe { sys::nftnl_chain_free(self.chain) };
     }
 }
+
+/// Returns a buffer containing a netlink message which requests a list of the chains in `table`.
+/// Kernels that don't support filtering the dump list the chains of all tables in the family, so
+/// callers should check [`ChainInfo::table`] of the result.
+///
+/// [`ChainInfo::table`]: query/struct.ChainInfo.html#structfield.table
+pub fn get_chains_nlmsg(seq: u32, table: &Table) -> Vec<u8> {
+    let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+    unsafe {
+        let header = sys::nftnl_nlmsg_build_hdr(
+            buffer.as_mut_ptr() as *mut c_char,
+            libc::NFT_MSG_GETCHAIN as u16,
+            table.get_family() as u16,
+            (libc::NLM_F_ROOT | libc::NLM_F_MATCH) as u16,
+            seq,
+        );
+        let chain = try_alloc!(sys::nftnl_chain_alloc());
+        sys::nftnl_chain_set_str(
+            chain,
+            sys::NFTNL_CHAIN_TABLE as u16,
+            table.get_name().as_ptr(),
+        );
+        sys::nftnl_chain_nlmsg_build_payload(header, chain);
+        sys::nftnl_chain_free(chain);
+    }
+    buffer
+}
+
+/// A callback to parse the response for messages created with `get_chains_nlmsg`. This callback
+/// appends the chains in the response to `chains`.
+pub fn get_chains_cb(header: &libc::nlmsghdr, chains: &mut Vec<ChainInfo>) -> libc::c_int {
+    match unsafe { crate::query::parse_chain(header) } {
+        Ok(chain) => {
+            chains.extend(chain);
+            1
+        }
+        Err(err) => {
+            error!("Failed to parse netlink chain message - {}", err);
+            -1
+        }
+    }
+}
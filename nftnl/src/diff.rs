@@ -0,0 +1,243 @@
+//! Computing the messages needed to turn one ruleset into another.
+//!
+//! Instead of flushing a table and adding all rules again on every configuration change, a
+//! [`RulesetDiff`] only deletes what is no longer wanted and adds what is missing. Rules at the
+//! start of a chain that are the same in both rulesets stay untouched, so their counters are kept,
+//! and the firewall is never in a partially loaded state.
+//!
+//! [`RulesetDiff`]: struct.RulesetDiff.html
+
+use crate::{Batch, Chain, MsgType, Rule, Table};
+
+/// The tables, chains and rules making up a ruleset. Can describe the whole firewall or only the
+/// parts of it an application manages.
+#[derive(Default)]
+pub struct Ruleset<'a> {
+    pub tables: Vec<&'a Table>,
+    pub chains: Vec<&'a Chain<'a>>,
+    pub rules: Vec<&'a Rule<'a>>,
+}
+
+/// One message of a [`RulesetDiff`].
+///
+/// [`RulesetDiff`]: struct.RulesetDiff.html
+pub enum BatchMsg<'a> {
+    Table(&'a Table, MsgType),
+    Chain(&'a Chain<'a>, MsgType),
+    Rule(&'a Rule<'a>, MsgType),
+}
+
+impl<'a> BatchMsg<'a> {
+    /// Adds this message to `batch`.
+    pub fn add_to(&self, batch: &mut Batch) {
+        match *self {
+            BatchMsg::Table(table, msg_type) => batch.add(table, msg_type),
+            BatchMsg::Chain(chain, msg_type) => batch.add(chain, msg_type),
+            BatchMsg::Rule(rule, msg_type) => batch.add(rule, msg_type),
+        }
+    }
+}
+
+/// Computes the add and delete messages between two [`Ruleset`]s.
+///
+/// [`Ruleset`]: struct.Ruleset.html
+pub struct RulesetDiff;
+
+impl RulesetDiff {
+    /// Returns the messages turning `current` into `desired`, in an order the kernel accepts them
+    /// in when added to one batch: first the deletions of rules, chains and tables, then the
+    /// additions of tables, chains and rules.
+    ///
+    /// Tables are identified by name and family and chains by name, table and family. Their other
+    /// attributes are not compared, so a chain whose policy changed is neither deleted nor added.
+    /// Rules are identified by their chain and their serialized expressions. Stateful
+    /// expressions, such as counters, are part of that, so rules with different counter values
+    /// are not equal.
+    ///
+    /// The order of the rules in a chain matters, so the rules of each chain in `current` and
+    /// `desired` are compared in order. The rules up to the first difference are kept, and all
+    /// rules of the chain from there on are deleted and added again in the desired order. Both
+    /// rulesets must list the rules of a chain in the order they have in the chain.
+    ///
+    /// Deleting a rule requires its handle, so the rules in `current` that might be deleted must
+    /// have been given one with [`Rule::set_handle`]. Added rules are appended to their chain.
+    ///
+    /// [`Rule::set_handle`]: ../struct.Rule.html#method.set_handle
+    pub fn compute<'a>(current: &Ruleset<'a>, desired: &Ruleset<'a>) -> Vec<BatchMsg<'a>> {
+        let mut messages = Vec::new();
+
+        let current_rules: Vec<_> = current.rules.iter().map(|rule| rule_key(rule)).collect();
+        let desired_rules: Vec<_> = desired.rules.iter().map(|rule| rule_key(rule)).collect();
+        let (removed_rules, added_rules) = changed_rules(&current_rules, &desired_rules);
+        let current_chains: Vec<_> = current
+            .chains
+            .iter()
+            .map(|chain| chain_key(chain))
+            .collect();
+        let desired_chains: Vec<_> = desired
+            .chains
+            .iter()
+            .map(|chain| chain_key(chain))
+            .collect();
+        let (removed_chains, added_chains) = unmatched(&current_chains, &desired_chains);
+        let current_tables: Vec<_> = current
+            .tables
+            .iter()
+            .map(|table| table_key(table))
+            .collect();
+        let desired_tables: Vec<_> = desired
+            .tables
+            .iter()
+            .map(|table| table_key(table))
+            .collect();
+        let (removed_tables, added_tables) = unmatched(&current_tables, &desired_tables);
+
+        for i in removed_rules {
+            messages.push(BatchMsg::Rule(current.rules[i], MsgType::Del));
+        }
+        for i in removed_chains {
+            messages.push(BatchMsg::Chain(current.chains[i], MsgType::Del));
+        }
+        for i in removed_tables {
+            messages.push(BatchMsg::Table(current.tables[i], MsgType::Del));
+        }
+        for i in added_tables {
+            messages.push(BatchMsg::Table(desired.tables[i], MsgType::Add));
+        }
+        for i in added_chains {
+            messages.push(BatchMsg::Chain(desired.chains[i], MsgType::Add));
+        }
+        for i in added_rules {
+            messages.push(BatchMsg::Rule(desired.rules[i], MsgType::Add));
+        }
+        messages
+    }
+}
+
+type TableKey = (Vec<u8>, u16);
+type ChainKey = (TableKey, Vec<u8>);
+
+fn table_key(table: &Table) -> TableKey {
+    (
+        table.get_name().to_bytes().to_vec(),
+        table.get_family() as u16,
+    )
+}
+
+fn chain_key(chain: &Chain<'_>) -> ChainKey {
+    (
+        table_key(chain.get_table()),
+        chain.get_name().to_bytes().to_vec(),
+    )
+}
+
+type RuleKey = (ChainKey, Vec<u8>);
+
+fn rule_key(rule: &Rule<'_>) -> RuleKey {
+    (chain_key(rule.get_chain()), rule.serialized_exprs())
+}
+
+/// Pairs up equal keys in `current` and `desired`, each key matching at most one other. Returns
+/// the indices of the keys left without a match in `current` and in `desired`.
+fn unmatched<K: PartialEq>(current: &[K], desired: &[K]) -> (Vec<usize>, Vec<usize>) {
+    let mut matched = vec![false; current.len()];
+    let mut added = Vec::new();
+    for (desired_index, key) in desired.iter().enumerate() {
+        let found = (0..current.len()).find(|&i| !matched[i] && current[i] == *key);
+        match found {
+            Some(i) => matched[i] = true,
+            None => added.push(desired_index),
+        }
+    }
+    let removed = (0..current.len()).filter(|&i| !matched[i]).collect();
+    (removed, added)
+}
+
+/// Compares the rules of each chain in `current` and `desired` in order. Returns the indices of
+/// the rules in `current` from the first difference in their chain onwards, which must be
+/// deleted, and the indices of the rules in `desired` from there on, which must be appended.
+fn changed_rules<C: PartialEq, R: PartialEq>(
+    current: &[(C, R)],
+    desired: &[(C, R)],
+) -> (Vec<usize>, Vec<usize>) {
+    let current_chains = group_by_chain(current);
+    let desired_chains = group_by_chain(desired);
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for (chain, current_rules) in &current_chains {
+        let desired_rules = rules_in(&desired_chains, chain);
+        let kept = current_rules
+            .iter()
+            .zip(desired_rules)
+            .take_while(|(&c, &d)| current[c].1 == desired[d].1)
+            .count();
+        removed.extend_from_slice(&current_rules[kept..]);
+        added.extend_from_slice(&desired_rules[kept..]);
+    }
+    for (chain, desired_rules) in &desired_chains {
+        if rules_in(&current_chains, chain).is_empty() {
+            added.extend_from_slice(desired_rules);
+        }
+    }
+    removed.sort_unstable();
+    added.sort_unstable();
+    (removed, added)
+}
+
+fn rules_in<'c, C: PartialEq>(chains: &'c [(&C, Vec<usize>)], chain: &C) -> &'c [usize] {
+    chains
+        .iter()
+        .find(|(key, _)| *key == chain)
+        .map_or(&[], |(_, rules)| rules)
+}
+
+/// Groups the indices of `rules` by chain, keeping the order of the rules within each chain.
+fn group_by_chain<C: PartialEq, R>(rules: &[(C, R)]) -> Vec<(&C, Vec<usize>)> {
+    let mut chains: Vec<(&C, Vec<usize>)> = Vec::new();
+    for (index, (chain, _)) in rules.iter().enumerate() {
+        match chains.iter_mut().find(|(key, _)| *key == chain) {
+            Some((_, indices)) => indices.push(index),
+            None => chains.push((chain, vec![index])),
+        }
+    }
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::changed_rules;
+
+    #[test]
+    fn unchanged_rules_are_kept() {
+        let rules = [("c", "a"), ("c", "b"), ("d", "a")];
+        assert_eq!(changed_rules(&rules, &rules), (vec![], vec![]));
+    }
+
+    #[test]
+    fn rule_added_before_existing_rule_re_adds_it() {
+        let current = [("c", "b")];
+        let desired = [("c", "a"), ("c", "b")];
+        assert_eq!(changed_rules(&current, &desired), (vec![0], vec![0, 1]));
+    }
+
+    #[test]
+    fn reordered_rules_are_re_added() {
+        let current = [("c", "accept"), ("c", "drop")];
+        let desired = [("c", "drop"), ("c", "accept")];
+        assert_eq!(changed_rules(&current, &desired), (vec![0, 1], vec![0, 1]));
+    }
+
+    #[test]
+    fn rules_after_first_difference_are_replaced() {
+        let current = [("c", "a"), ("c", "b"), ("c", "c")];
+        let desired = [("c", "a"), ("c", "x"), ("c", "c")];
+        assert_eq!(changed_rules(&current, &desired), (vec![1, 2], vec![1, 2]));
+    }
+
+    #[test]
+    fn chains_are_compared_separately() {
+        let current = [("c", "a"), ("d", "a"), ("d", "b")];
+        let desired = [("d", "a"), ("e", "a"), ("c", "a")];
+        assert_eq!(changed_rules(&current, &desired), (vec![2], vec![1]));
+    }
+}
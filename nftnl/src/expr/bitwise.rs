@@ -1,6 +1,6 @@
-use super::{Expression, Rule};
+use super::{Expression, Register, Rule};
 use crate::expr::cmp::ToSlice;
-use nftnl_sys::{self as sys, libc};
+use nftnl_sys as sys;
 use std::ffi::c_void;
 use std::os::raw::c_char;
 
@@ -8,13 +8,32 @@ use std::os::raw::c_char;
 pub struct Bitwise<M: ToSlice, X: ToSlice> {
     mask: M,
     xor: X,
+    src_reg: Register,
+    dest_reg: Register,
 }
 
 impl<M: ToSlice, X: ToSlice> Bitwise<M, X> {
     /// Returns a new `Bitwise` instance that first masks the value it's applied to with `mask`
     /// and then performs xor with the value in `xor`.
+    ///
+    /// Both the source and the destination register are [`Register::Reg1`].
+    ///
+    /// [`Register::Reg1`]: enum.Register.html#variant.Reg1
     pub fn new(mask: M, xor: X) -> Self {
-        Self { mask, xor }
+        Self::with_regs(mask, xor, Register::Reg1, Register::Reg1)
+    }
+
+    /// Returns a new `Bitwise` instance like [`new`] does, but reading its input from `src`
+    /// and storing the result in `dest`.
+    ///
+    /// [`new`]: #method.new
+    pub fn with_regs(mask: M, xor: X, src: Register, dest: Register) -> Self {
+        Self {
+            mask,
+            xor,
+            src_reg: src,
+            dest_reg: dest,
+        }
     }
 }
 
@@ -33,12 +52,12 @@ impl<M: ToSlice, X: ToSlice> Expression for Bitwise<M, X> {
             sys::nftnl_expr_set_u32(
                 expr,
                 sys::NFTNL_EXPR_BITWISE_SREG as u16,
-                libc::NFT_REG_1 as u32,
+                self.src_reg.to_raw(),
             );
             sys::nftnl_expr_set_u32(
                 expr,
                 sys::NFTNL_EXPR_BITWISE_DREG as u16,
-                libc::NFT_REG_1 as u32,
+                self.dest_reg.to_raw(),
             );
             sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_BITWISE_LEN as u16, len);
 
@@ -65,4 +84,7 @@ macro_rules! nft_expr_bitwise {
     (mask $mask:expr,xor $xor:expr) => {
         $crate::expr::Bitwise::new($mask, $xor)
     };
+    (mask $mask:expr,xor $xor:expr,sreg $sreg:expr,dreg $dreg:expr) => {
+        $crate::expr::Bitwise::with_regs($mask, $xor, $sreg, $dreg)
+    };
 }
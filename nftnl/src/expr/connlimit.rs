@@ -0,0 +1,40 @@
+use super::{Expression, Rule};
+use nftnl_sys as sys;
+use std::os::raw::c_char;
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_CONNLIMIT_F_INV: u32 = 1;
+
+/// Matches on the number of tracked connections, `ct count` in `nft`. Used directly in a rule
+/// it counts all connections that have passed the rule. Used in a set element, for example of
+/// a set keyed on the source address, it counts the connections per element.
+///
+/// Requires the `nftnl-1-1-1` feature and Linux 4.18 or newer.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ConnLimit {
+    /// The number of connections to compare with.
+    pub count: u32,
+    /// When `false` the expression matches while there are at most `count` connections. When
+    /// `true` it matches when there are more than `count` connections, `ct count over` in
+    /// `nft`.
+    pub over: bool,
+}
+
+impl Expression for ConnLimit {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"connlimit\0" as *const _ as *const c_char
+            ));
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_CONNLIMIT_COUNT as u16, self.count);
+            if self.over {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_CONNLIMIT_FLAGS as u16,
+                    NFT_CONNLIMIT_F_INV,
+                );
+            }
+            expr
+        }
+    }
+}
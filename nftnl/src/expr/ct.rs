@@ -1,6 +1,14 @@
-use super::{Expression, Rule};
+use super::{Expression, Rule, ToSlice};
 use nftnl_sys::{self as sys, libc};
-use std::os::raw::c_char;
+use std::{borrow::Cow, os::raw::c_char};
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_CT_ZONE: u32 = 17;
+const NFT_CT_ID: u32 = 23;
+
+// From linux/netfilter/nf_conntrack_tuple_common.h.
+const IP_CT_DIR_ORIGINAL: u8 = 0;
+const IP_CT_DIR_REPLY: u8 = 1;
 
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -35,10 +43,130 @@ bitflags::bitflags! {
     }
 }
 
+/// The 128 bit label of a conntrack entry, as loaded by [`Conntrack::Label`]. Each of the 128
+/// bits is a separate label, bit `n` being the label numbered `n` in `connlabel.conf`. Can be
+/// compared with [`Cmp`] and stored with `ct label set`.
+///
+/// [`Conntrack::Label`]: enum.Conntrack.html#variant.Label
+/// [`Cmp`]: struct.Cmp.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ConntrackLabel(pub [u8; 16]);
+
+impl ToSlice for ConntrackLabel {
+    fn to_slice(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+}
+
+/// The direction of a packet relative to its connection, as loaded by
+/// [`Conntrack::Direction`].
+///
+/// [`Conntrack::Direction`]: enum.Conntrack.html#variant.Direction
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum CtDirection {
+    /// The packet travels in the direction of the packet that created the connection.
+    Original = IP_CT_DIR_ORIGINAL,
+    /// The packet travels in the opposite direction, i.e. it is a reply.
+    Reply = IP_CT_DIR_REPLY,
+}
+
+impl ToSlice for CtDirection {
+    fn to_slice(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(vec![*self as u8])
+    }
+}
+
+/// A conntrack expression. Loads information about the connection tracking entry of a packet
+/// into the register, or stores data from the register into the entry.
 pub enum Conntrack {
+    /// The connection state, see [`States`].
+    ///
+    /// [`States`]: struct.States.html
     State,
+    /// The connection status, see [`ConntrackStatus`].
+    ///
+    /// [`ConntrackStatus`]: struct.ConntrackStatus.html
     Status,
+    /// The 8 bit direction of the packet, see [`CtDirection`].
+    ///
+    /// [`CtDirection`]: enum.CtDirection.html
+    Direction,
+    /// The connection mark. Loads the mark into the register, or stores the register value as
+    /// the new mark if `set` is `true`.
+    ///
+    /// There is no masked variant of this key in nftables. Masking is instead done with a
+    /// [`Bitwise`] expression on the register. A masked match, `ct mark and 0xff00 == 0x0100`, is
+    /// written as:
+    /// ```ignore
+    /// rule.add_expr(&nft_expr!(ct mark));
+    /// rule.add_expr(&nft_expr!(bitwise mask 0xff00u32, xor 0u32));
+    /// rule.add_expr(&nft_expr!(cmp == 0x0100u32));
+    /// ```
+    /// Setting only some bits of the mark, `ct mark set ct mark and 0xffff00ff or 0x0100`, loads
+    /// the current mark, clears the bits with the mask, sets the new ones with the xor and then
+    /// stores the result:
+    /// ```ignore
+    /// rule.add_expr(&nft_expr!(ct mark));
+    /// rule.add_expr(&nft_expr!(bitwise mask 0xffff00ffu32, xor 0x0100u32));
+    /// rule.add_expr(&nft_expr!(ct mark set));
+    /// ```
+    ///
+    /// [`Bitwise`]: struct.Bitwise.html
     Mark { set: bool },
+    /// The connection labels, see [`ConntrackLabel`]. Loads all 16 bytes into the register, or
+    /// stores the register value as the new labels if `set` is `true`.
+    ///
+    /// [`ConntrackLabel`]: struct.ConntrackLabel.html
+    Label { set: bool },
+    /// The 16 bit conntrack zone. Loads the zone into the register, or stores the register
+    /// value as the zone if `set` is `true`. Setting the zone is only possible before the
+    /// connection is tracked, i.e. in a chain with a priority below
+    /// [`priority::CONNTRACK`], such as [`priority::RAW`]. Requires Linux 4.10 or newer.
+    ///
+    /// [`priority::CONNTRACK`]: ../priority/constant.CONNTRACK.html
+    /// [`priority::RAW`]: ../priority/constant.RAW.html
+    Zone { set: bool },
+    /// The source address of the original direction of the connection, i.e. the address before
+    /// any source NAT. Loads 4 bytes in an `ip` table and 16 bytes in an `ip6` or `inet` table.
+    OrigSrcAddr,
+    /// The destination address of the original direction of the connection, i.e. the address
+    /// before any destination NAT. Sized like [`OrigSrcAddr`].
+    ///
+    /// [`OrigSrcAddr`]: #variant.OrigSrcAddr
+    OrigDstAddr,
+    /// The source address of the reply direction of the connection. Sized like [`OrigSrcAddr`].
+    ///
+    /// [`OrigSrcAddr`]: #variant.OrigSrcAddr
+    ReplySrcAddr,
+    /// The destination address of the reply direction of the connection. Sized like
+    /// [`OrigSrcAddr`].
+    ///
+    /// [`OrigSrcAddr`]: #variant.OrigSrcAddr
+    ReplyDstAddr,
+    /// The 16 bit source port, in network byte order, of the original direction of the
+    /// connection.
+    OrigProtoSrc,
+    /// The 16 bit destination port, in network byte order, of the original direction of the
+    /// connection.
+    OrigProtoDst,
+    /// The 16 bit source port, in network byte order, of the reply direction of the connection.
+    ReplyProtoSrc,
+    /// The 16 bit destination port, in network byte order, of the reply direction of the
+    /// connection.
+    ReplyProtoDst,
+    /// The time until the conntrack entry expires, as a 32 bit number of milliseconds.
+    ///
+    /// The number of connections, `ct count` in `nft`, is not a conntrack key but a separate
+    /// expression, see `ConnLimit`.
+    Expiration,
+    /// The name of the conntrack helper assigned to the connection, such as `ftp` or `sip`.
+    /// Loads 16 bytes, so compare it with a nul terminated string:
+    /// `nft_expr!(cmp == "ftp\0")`.
+    Helper,
+    /// The 32 bit ID of the conntrack entry. The same ID userspace conntrack tools, such as
+    /// `conntrack -L -o id`, identify the connection by. Requires Linux 5.3 or newer.
+    Id,
 }
 
 impl Conntrack {
@@ -46,9 +174,43 @@ impl Conntrack {
         match *self {
             Conntrack::State => libc::NFT_CT_STATE as u32,
             Conntrack::Status => libc::NFT_CT_STATUS as u32,
+            Conntrack::Direction => libc::NFT_CT_DIRECTION as u32,
             Conntrack::Mark { .. } => libc::NFT_CT_MARK as u32,
+            Conntrack::Label { .. } => libc::NFT_CT_LABELS as u32,
+            Conntrack::Zone { .. } => NFT_CT_ZONE,
+            Conntrack::OrigSrcAddr | Conntrack::ReplySrcAddr => libc::NFT_CT_SRC as u32,
+            Conntrack::OrigDstAddr | Conntrack::ReplyDstAddr => libc::NFT_CT_DST as u32,
+            Conntrack::OrigProtoSrc | Conntrack::ReplyProtoSrc => libc::NFT_CT_PROTO_SRC as u32,
+            Conntrack::OrigProtoDst | Conntrack::ReplyProtoDst => libc::NFT_CT_PROTO_DST as u32,
+            Conntrack::Expiration => libc::NFT_CT_EXPIRATION as u32,
+            Conntrack::Helper => libc::NFT_CT_HELPER as u32,
+            Conntrack::Id => NFT_CT_ID,
+        }
+    }
+
+    /// The tuple direction the key is read from, for keys that need one.
+    fn direction(&self) -> Option<u8> {
+        match *self {
+            Conntrack::OrigSrcAddr
+            | Conntrack::OrigDstAddr
+            | Conntrack::OrigProtoSrc
+            | Conntrack::OrigProtoDst => Some(IP_CT_DIR_ORIGINAL),
+            Conntrack::ReplySrcAddr
+            | Conntrack::ReplyDstAddr
+            | Conntrack::ReplyProtoSrc
+            | Conntrack::ReplyProtoDst => Some(IP_CT_DIR_REPLY),
+            _ => None,
         }
     }
+
+    fn is_set(&self) -> bool {
+        matches!(
+            *self,
+            Conntrack::Mark { set: true }
+                | Conntrack::Label { set: true }
+                | Conntrack::Zone { set: true }
+        )
+    }
 }
 
 impl Expression for Conntrack {
@@ -56,7 +218,7 @@ impl Expression for Conntrack {
         unsafe {
             let expr = try_alloc!(sys::nftnl_expr_alloc(b"ct\0" as *const _ as *const c_char));
 
-            if let Conntrack::Mark { set: true } = self {
+            if self.is_set() {
                 sys::nftnl_expr_set_u32(
                     expr,
                     sys::NFTNL_EXPR_CT_SREG as u16,
@@ -70,6 +232,9 @@ impl Expression for Conntrack {
                 );
             }
             sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_CT_KEY as u16, self.raw_key());
+            if let Some(direction) = self.direction() {
+                sys::nftnl_expr_set_u8(expr, sys::NFTNL_EXPR_CT_DIR as u16, direction);
+            }
 
             expr
         }
@@ -84,10 +249,70 @@ macro_rules! nft_expr_ct {
     (status) => {
         $crate::expr::Conntrack::Status
     };
+    (direction) => {
+        $crate::expr::Conntrack::Direction
+    };
     (mark set) => {
         $crate::expr::Conntrack::Mark { set: true }
     };
     (mark) => {
         $crate::expr::Conntrack::Mark { set: false }
     };
+    (label set) => {
+        $crate::expr::Conntrack::Label { set: true }
+    };
+    (label) => {
+        $crate::expr::Conntrack::Label { set: false }
+    };
+    (zone set) => {
+        $crate::expr::Conntrack::Zone { set: true }
+    };
+    (zone) => {
+        $crate::expr::Conntrack::Zone { set: false }
+    };
+    (original saddr) => {
+        $crate::expr::Conntrack::OrigSrcAddr
+    };
+    (original daddr) => {
+        $crate::expr::Conntrack::OrigDstAddr
+    };
+    (reply saddr) => {
+        $crate::expr::Conntrack::ReplySrcAddr
+    };
+    (reply daddr) => {
+        $crate::expr::Conntrack::ReplyDstAddr
+    };
+    (original proto-src) => {
+        $crate::expr::Conntrack::OrigProtoSrc
+    };
+    (original proto-dst) => {
+        $crate::expr::Conntrack::OrigProtoDst
+    };
+    (reply proto-src) => {
+        $crate::expr::Conntrack::ReplyProtoSrc
+    };
+    (reply proto-dst) => {
+        $crate::expr::Conntrack::ReplyProtoDst
+    };
+    (expiration) => {
+        $crate::expr::Conntrack::Expiration
+    };
+    (count over $count:expr) => {
+        $crate::expr::ConnLimit {
+            count: $count,
+            over: true,
+        }
+    };
+    (count $count:expr) => {
+        $crate::expr::ConnLimit {
+            count: $count,
+            over: false,
+        }
+    };
+    (helper) => {
+        $crate::expr::Conntrack::Helper
+    };
+    (id) => {
+        $crate::expr::Conntrack::Id
+    };
 }
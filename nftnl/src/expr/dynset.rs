@@ -0,0 +1,92 @@
+use super::{Expression, Register, Rule};
+use crate::set::DynamicSet;
+use nftnl_sys::{self as sys, libc};
+use std::{ffi::CString, os::raw::c_char};
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_DYNSET_OP_DELETE: u32 = 2;
+
+/// What a [`SetUpdate`] does with the key.
+///
+/// [`SetUpdate`]: struct.SetUpdate.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DynsetOp {
+    /// Adds the key to the set, unless it is already there.
+    Add,
+    /// Adds the key to the set, or refreshes its timeout if it is already there.
+    Update,
+    /// Deletes the key from the set. Requires Linux 5.13 or newer.
+    Delete,
+}
+
+impl DynsetOp {
+    /// Returns the corresponding `NFT_DYNSET_OP_*` constant for this operation.
+    pub fn to_raw(self) -> u32 {
+        match self {
+            DynsetOp::Add => libc::NFT_DYNSET_OP_ADD as u32,
+            DynsetOp::Update => libc::NFT_DYNSET_OP_UPDATE as u32,
+            DynsetOp::Delete => NFT_DYNSET_OP_DELETE,
+        }
+    }
+}
+
+/// Adds the value in `src_reg` to, or deletes it from, a [`DynamicSet`] from the packet path,
+/// `add @set { ip saddr }` in `nft`.
+///
+/// [`DynamicSet`]: ../set/struct.DynamicSet.html
+pub struct SetUpdate {
+    pub set_name: CString,
+    pub set_id: u32,
+    pub op: DynsetOp,
+    pub src_reg: Register,
+}
+
+impl SetUpdate {
+    /// Returns an update of `set` with the value in `Register::Reg1`.
+    pub fn new<K>(op: DynsetOp, set: &DynamicSet<'_, K>) -> Self {
+        SetUpdate {
+            set_name: set.as_set().get_name().to_owned(),
+            set_id: set.as_set().get_id(),
+            op,
+            src_reg: Register::Reg1,
+        }
+    }
+}
+
+impl Expression for SetUpdate {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"dynset\0" as *const _ as *const c_char
+            ));
+
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_DYNSET_SREG_KEY as u16,
+                self.src_reg.to_raw(),
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_DYNSET_OP as u16, self.op.to_raw());
+            sys::nftnl_expr_set_str(
+                expr,
+                sys::NFTNL_EXPR_DYNSET_SET_NAME as u16,
+                self.set_name.as_ptr(),
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_DYNSET_SET_ID as u16, self.set_id);
+
+            expr
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! nft_expr_dynset {
+    (add @$set:ident) => {
+        $crate::expr::SetUpdate::new($crate::expr::DynsetOp::Add, &$set)
+    };
+    (update @$set:ident) => {
+        $crate::expr::SetUpdate::new($crate::expr::DynsetOp::Update, &$set)
+    };
+    (delete @$set:ident) => {
+        $crate::expr::SetUpdate::new($crate::expr::DynsetOp::Delete, &$set)
+    };
+}
@@ -0,0 +1,132 @@
+use super::{Expression, Rule};
+use nftnl_sys::{self as sys, libc};
+use std::os::raw::c_char;
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFTA_FIB_F_SADDR: u32 = 1 << 0;
+const NFTA_FIB_F_DADDR: u32 = 1 << 1;
+
+bitflags::bitflags! {
+    /// Extra keys to include in a [`Fib`] lookup, in addition to the address.
+    ///
+    /// [`Fib`]: enum.Fib.html
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct FibFlags: u32 {
+        /// Use the packet mark in the lookup.
+        const MARK = 1 << 2;
+        /// Use the input interface in the lookup.
+        const IIF = 1 << 3;
+        /// Use the output interface in the lookup.
+        const OIF = 1 << 4;
+        /// Only check whether a route exists, loading a boolean instead of the result.
+        const PRESENT = 1 << 5;
+    }
+}
+
+/// What a [`Fib`] lookup loads into the register.
+///
+/// [`Fib`]: enum.Fib.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(u32)]
+pub enum FibResult {
+    /// Index of the output interface of the route.
+    Oif = 1,
+    /// Name of the output interface of the route.
+    OifName = 2,
+    /// Type of the address, such as local, unicast or broadcast.
+    AddrType = 3,
+}
+
+/// A forwarding information base (routing table) lookup for the address of the packet. Commonly
+/// used for reverse path filtering, `fib saddr . iif oif`, which with the macro is written as
+/// `nft_expr!(fib saddr . iif Oif)`. The result is given as a [`FibResult`] variant, so typos are
+/// caught at compile time.
+///
+/// Requires the `nftnl-1-0-7` feature.
+///
+/// [`FibResult`]: enum.FibResult.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Fib {
+    /// Look up the source address of the packet.
+    Saddr { result: FibResult, flags: FibFlags },
+    /// Look up the destination address of the packet.
+    Daddr { result: FibResult, flags: FibFlags },
+}
+
+impl Fib {
+    fn raw_flags(&self) -> u32 {
+        match *self {
+            Fib::Saddr { flags, .. } => NFTA_FIB_F_SADDR | flags.bits(),
+            Fib::Daddr { flags, .. } => NFTA_FIB_F_DADDR | flags.bits(),
+        }
+    }
+
+    fn result(&self) -> FibResult {
+        match *self {
+            Fib::Saddr { result, .. } | Fib::Daddr { result, .. } => result,
+        }
+    }
+}
+
+impl Expression for Fib {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(b"fib\0" as *const _ as *const c_char));
+
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_FIB_DREG as u16,
+                libc::NFT_REG_1 as u32,
+            );
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_FIB_RESULT as u16,
+                self.result() as u32,
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_FIB_FLAGS as u16, self.raw_flags());
+
+            expr
+        }
+    }
+}
+
+#[macro_export(local_inner_macros)]
+macro_rules! nft_expr_fib {
+    (@flag mark) => {
+        $crate::expr::FibFlags::MARK
+    };
+    (@flag iif) => {
+        $crate::expr::FibFlags::IIF
+    };
+    (@flag oif) => {
+        $crate::expr::FibFlags::OIF
+    };
+    (@flag present) => {
+        $crate::expr::FibFlags::PRESENT
+    };
+
+    (saddr $result:ident) => {
+        $crate::expr::Fib::Saddr {
+            result: $crate::expr::FibResult::$result,
+            flags: $crate::expr::FibFlags::empty(),
+        }
+    };
+    (saddr . $($flag:ident).+ $result:ident) => {
+        $crate::expr::Fib::Saddr {
+            result: $crate::expr::FibResult::$result,
+            flags: $crate::expr::FibFlags::empty() $(| nft_expr_fib!(@flag $flag))+,
+        }
+    };
+    (daddr $result:ident) => {
+        $crate::expr::Fib::Daddr {
+            result: $crate::expr::FibResult::$result,
+            flags: $crate::expr::FibFlags::empty(),
+        }
+    };
+    (daddr . $($flag:ident).+ $result:ident) => {
+        $crate::expr::Fib::Daddr {
+            result: $crate::expr::FibResult::$result,
+            flags: $crate::expr::FibFlags::empty() $(| nft_expr_fib!(@flag $flag))+,
+        }
+    };
+}
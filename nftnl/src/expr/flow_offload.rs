@@ -0,0 +1,31 @@
+use super::{Expression, Rule};
+use nftnl_sys as sys;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+/// Adds the connection of the packet to a flowtable, so the following packets of it are
+/// forwarded by the flowtable, bypassing the rest of the ruleset. `flow add @name` in `nft`. Used
+/// in `forward` chains. The flowtable must already exist in the table of the rule.
+///
+/// Can be created with `nft_expr!(flow add @name)`, where `name` is a `&CStr` or a `CString`.
+///
+/// Requires the `nftnl-1-1-0` feature.
+pub struct FlowOffload {
+    pub flowtable_name: CString,
+}
+
+impl Expression for FlowOffload {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"flow_offload\0" as *const _ as *const c_char
+            ));
+            sys::nftnl_expr_set_str(
+                expr,
+                sys::NFTNL_EXPR_FLOW_TABLE_NAME as u16,
+                self.flowtable_name.as_ptr(),
+            );
+            expr
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::{Expression, Register, Rule};
+use nftnl_sys::{self as sys, libc};
+use std::os::raw::c_char;
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_HASH_JENKINS: u32 = 0;
+const NFT_HASH_SYM: u32 = 1;
+
+/// The hash function of a [`Hash`] expression.
+///
+/// [`Hash`]: struct.Hash.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HashType {
+    /// Jenkins hash of data in a register, `jhash` in `nft`.
+    Jhash,
+    /// Hash of the flow, giving the same value for both directions of a connection. `symhash` in
+    /// `nft`. Does not read any register, and ignores the seed.
+    Symhash,
+}
+
+/// A hash expression. Loads `hash % modulus + offset` into the register. Used to spread
+/// connections over several targets, for example with `nft_expr!(hash mod 4 seed 0)` followed by
+/// a [`Cmp`] or a map lookup.
+///
+/// Requires the `nftnl-1-0-8` feature.
+///
+/// [`Cmp`]: struct.Cmp.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Hash {
+    pub hash_type: HashType,
+    pub modulus: u32,
+    pub seed: u32,
+    pub offset: u32,
+    /// The register and the number of bytes in it to hash. Required for `Jhash`, where the data,
+    /// such as an address, is first loaded by for example a [`Payload`] expression.
+    ///
+    /// [`Payload`]: enum.Payload.html
+    pub source: Option<(Register, u32)>,
+}
+
+impl Expression for Hash {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"hash\0" as *const _ as *const c_char
+            ));
+
+            let hash_type = match self.hash_type {
+                HashType::Jhash => NFT_HASH_JENKINS,
+                HashType::Symhash => NFT_HASH_SYM,
+            };
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_HASH_TYPE as u16, hash_type);
+            if let Some((register, len)) = self.source {
+                sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_HASH_SREG as u16, register.to_raw());
+                sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_HASH_LEN as u16, len);
+            }
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_HASH_DREG as u16,
+                libc::NFT_REG_1 as u32,
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_HASH_MODULUS as u16, self.modulus);
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_HASH_SEED as u16, self.seed);
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_HASH_OFFSET as u16, self.offset);
+
+            expr
+        }
+    }
+}
+
+/// `hash mod N seed S` hashes the four bytes in register 1, such as an IPv4 address loaded just
+/// before. `hash len L mod N seed S` hashes `L` bytes instead.
+#[macro_export]
+macro_rules! nft_expr_hash {
+    (len $len:tt mod $modulus:tt seed $seed:tt) => {
+        $crate::expr::Hash {
+            hash_type: $crate::expr::HashType::Jhash,
+            modulus: $modulus,
+            seed: $seed,
+            offset: 0,
+            source: Some(($crate::expr::Register::Reg1, $len)),
+        }
+    };
+    (mod $modulus:tt seed $seed:tt) => {
+        $crate::nft_expr_hash!(len 4 mod $modulus seed $seed)
+    };
+    (symhash mod $modulus:tt) => {
+        $crate::expr::Hash {
+            hash_type: $crate::expr::HashType::Symhash,
+            modulus: $modulus,
+            seed: 0,
+            offset: 0,
+            source: None,
+        }
+    };
+}
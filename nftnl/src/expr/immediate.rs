@@ -2,6 +2,7 @@ use super::{Expression, Register, Rule};
 use nftnl_sys as sys;
 use std::ffi::c_void;
 use std::mem::size_of_val;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::os::raw::c_char;
 
 /// An immediate expression. Used to set immediate data.
@@ -18,6 +19,20 @@ impl<T> Immediate<T> {
     }
 }
 
+impl Immediate<[u8; 4]> {
+    /// Loads an IPv4 address, in network byte order, into `register`.
+    pub fn from_ipv4(addr: Ipv4Addr, register: Register) -> Self {
+        Self::new(addr.octets(), register)
+    }
+}
+
+impl Immediate<[u8; 16]> {
+    /// Loads an IPv6 address, in network byte order, into `register`.
+    pub fn from_ipv6(addr: Ipv6Addr, register: Register) -> Self {
+        Self::new(addr.octets(), register)
+    }
+}
+
 impl<T> Expression for Immediate<T> {
     fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
         unsafe {
@@ -51,4 +66,24 @@ macro_rules! nft_expr_immediate {
             register: $crate::expr::Register::Reg1,
         }
     };
+    (ipv4 $addr:expr) => {
+        $crate::expr::Immediate::from_ipv4($addr, $crate::expr::Register::Reg1)
+    };
+    (ipv6 $addr:expr) => {
+        $crate::expr::Immediate::from_ipv6($addr, $crate::expr::Register::Reg1)
+    };
+    // Ports are compared against packet payload, so they are loaded in network byte order.
+    (port $port:expr) => {
+        $crate::expr::Immediate::new(u16::to_be_bytes($port), $crate::expr::Register::Reg1)
+    };
+    // Typed bindings instead of casts, so values of the wrong type fail to compile instead of
+    // being truncated.
+    (u32 $value:expr) => {{
+        let value: u32 = $value;
+        $crate::expr::Immediate::new(value, $crate::expr::Register::Reg1)
+    }};
+    (u64 $value:expr) => {{
+        let value: u64 = $value;
+        $crate::expr::Immediate::new(value, $crate::expr::Register::Reg1)
+    }};
 }
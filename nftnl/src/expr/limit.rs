@@ -0,0 +1,167 @@
+use super::{Expression, Rule};
+use nftnl_sys as sys;
+use std::os::raw::c_char;
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_LIMIT_PKTS: u32 = 0;
+const NFT_LIMIT_PKT_BYTES: u32 = 1;
+pub(crate) const NFT_LIMIT_F_INV: u32 = 1;
+
+/// What a [`Limit`] counts.
+///
+/// [`Limit`]: struct.Limit.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LimitUnit {
+    Packets,
+    Bytes,
+}
+
+impl LimitUnit {
+    /// Returns the `NFT_LIMIT_*` type counting this unit.
+    pub(crate) fn to_raw(self) -> u32 {
+        match self {
+            LimitUnit::Packets => NFT_LIMIT_PKTS,
+            LimitUnit::Bytes => NFT_LIMIT_PKT_BYTES,
+        }
+    }
+}
+
+/// The time period the rate of a [`Limit`] is given per.
+///
+/// [`Limit`]: struct.Limit.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LimitPer {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl LimitPer {
+    /// Returns the length of this period in seconds.
+    pub fn seconds(self) -> u64 {
+        match self {
+            LimitPer::Second => 1,
+            LimitPer::Minute => 60,
+            LimitPer::Hour => 60 * 60,
+            LimitPer::Day => 24 * 60 * 60,
+        }
+    }
+}
+
+/// A limit expression. Matches packets until `rate` packets or bytes `per` time period have
+/// matched, using a token bucket. `burst` is how many packets or bytes above the rate are
+/// allowed to match in a short burst.
+///
+/// `nft_expr!(limit rate 100 packets per second)` and
+/// `nft_expr!(limit rate 1 mbytes per second burst 10 mbytes)` are the same as in `nft`. With
+/// `rate over`, the match is inverted and only the packets exceeding the rate match.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Limit {
+    pub rate: u64,
+    pub unit: LimitUnit,
+    pub per: LimitPer,
+    pub burst: u32,
+    /// Match the packets above the rate instead of the ones within it.
+    pub inverse: bool,
+}
+
+impl Limit {
+    /// Creates a limit with the same default burst as `nft` uses: five packets, or no burst at
+    /// all when limiting bytes.
+    pub fn new(rate: u64, unit: LimitUnit, per: LimitPer) -> Self {
+        let burst = match unit {
+            LimitUnit::Packets => 5,
+            LimitUnit::Bytes => 0,
+        };
+        Limit {
+            rate,
+            unit,
+            per,
+            burst,
+            inverse: false,
+        }
+    }
+}
+
+impl Expression for Limit {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"limit\0" as *const _ as *const c_char
+            ));
+
+            sys::nftnl_expr_set_u64(expr, sys::NFTNL_EXPR_LIMIT_RATE as u16, self.rate);
+            sys::nftnl_expr_set_u64(expr, sys::NFTNL_EXPR_LIMIT_UNIT as u16, self.per.seconds());
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LIMIT_BURST as u16, self.burst);
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LIMIT_TYPE as u16, self.unit.to_raw());
+            if self.inverse {
+                sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LIMIT_FLAGS as u16, NFT_LIMIT_F_INV);
+            }
+
+            expr
+        }
+    }
+}
+
+#[macro_export(local_inner_macros)]
+macro_rules! nft_expr_limit {
+    (@unit packets) => {
+        $crate::expr::LimitUnit::Packets
+    };
+    (@unit bytes) => {
+        $crate::expr::LimitUnit::Bytes
+    };
+    (@unit kbytes) => {
+        $crate::expr::LimitUnit::Bytes
+    };
+    (@unit mbytes) => {
+        $crate::expr::LimitUnit::Bytes
+    };
+
+    (@multiplier packets) => {
+        1
+    };
+    (@multiplier bytes) => {
+        1
+    };
+    (@multiplier kbytes) => {
+        1024
+    };
+    (@multiplier mbytes) => {
+        1024 * 1024
+    };
+
+    (@per second) => {
+        $crate::expr::LimitPer::Second
+    };
+    (@per minute) => {
+        $crate::expr::LimitPer::Minute
+    };
+    (@per hour) => {
+        $crate::expr::LimitPer::Hour
+    };
+    (@per day) => {
+        $crate::expr::LimitPer::Day
+    };
+
+    (rate over $($tail:tt)+) => {
+        $crate::expr::Limit {
+            inverse: true,
+            ..nft_expr_limit!(rate $($tail)+)
+        }
+    };
+    (rate $rate:tt $unit:ident per $per:ident burst $burst:tt $burst_unit:ident) => {
+        $crate::expr::Limit {
+            burst: ($burst as u32) * nft_expr_limit!(@multiplier $burst_unit),
+            ..nft_expr_limit!(rate $rate $unit per $per)
+        }
+    };
+    (rate $rate:tt $unit:ident per $per:ident) => {
+        $crate::expr::Limit::new(
+            ($rate as u64) * nft_expr_limit!(@multiplier $unit),
+            nft_expr_limit!(@unit $unit),
+            nft_expr_limit!(@per $per),
+        )
+    };
+}
@@ -1,5 +1,6 @@
+use super::Register;
 use super::{Expression, Rule};
-use crate::set::Set;
+use crate::set::{DataMap, Set, VerdictMap};
 use nftnl_sys::{self as sys, libc};
 use std::ffi::CString;
 use std::os::raw::c_char;
@@ -7,6 +8,8 @@ use std::os::raw::c_char;
 pub struct Lookup {
     set_name: CString,
     set_id: u32,
+    #[cfg(feature = "nftnl-1-0-7")]
+    invert: bool,
 }
 
 impl Lookup {
@@ -14,6 +17,19 @@ impl Lookup {
         Lookup {
             set_name: set.get_name().to_owned(),
             set_id: set.get_id(),
+            #[cfg(feature = "nftnl-1-0-7")]
+            invert: false,
+        }
+    }
+
+    /// Returns a lookup that matches when the value is *not* in `set`, `!= @set` in `nft`.
+    ///
+    /// Requires the `nftnl-1-0-7` feature.
+    #[cfg(feature = "nftnl-1-0-7")]
+    pub fn new_inverted<K>(set: &Set<'_, K>) -> Self {
+        Lookup {
+            invert: true,
+            ..Lookup::new(set)
         }
     }
 }
@@ -36,12 +52,113 @@ impl Expression for Lookup {
                 self.set_name.as_ptr() as *const _ as *const c_char,
             );
             sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LOOKUP_SET_ID as u16, self.set_id);
+            #[cfg(feature = "nftnl-1-0-7")]
+            if self.invert {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_LOOKUP_FLAGS as u16,
+                    libc::NFT_LOOKUP_F_INV as u32,
+                );
+            }
+
+            expr
+        }
+    }
+}
+
+/// Looks up the value in `src_reg` in a [`VerdictMap`] and applies the verdict of the matching
+/// element, `vmap` in `nft`. Packets whose value is not in the map continue with the next
+/// expression.
+///
+/// [`VerdictMap`]: ../set/struct.VerdictMap.html
+pub struct VmapLookup {
+    pub set_name: CString,
+    pub set_id: u32,
+    pub src_reg: Register,
+}
+
+impl VmapLookup {
+    /// Returns a lookup of the value in `Register::Reg1` in `map`.
+    pub fn new<K>(map: &VerdictMap<'_, K>) -> Self {
+        VmapLookup {
+            set_name: map.as_set().get_name().to_owned(),
+            set_id: map.as_set().get_id(),
+            src_reg: Register::Reg1,
+        }
+    }
+}
 
-            // This code is left here since it's quite likely we need it again when we get further
-            // if self.reverse {
-            //     sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LOOKUP_FLAGS as u16,
-            //         libc::NFT_LOOKUP_F_INV as u32);
-            // }
+impl Expression for VmapLookup {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"lookup\0" as *const _ as *const c_char
+            ));
+
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_LOOKUP_SREG as u16,
+                self.src_reg.to_raw(),
+            );
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_LOOKUP_DREG as u16,
+                libc::NFT_REG_VERDICT as u32,
+            );
+            sys::nftnl_expr_set_str(
+                expr,
+                sys::NFTNL_EXPR_LOOKUP_SET as u16,
+                self.set_name.as_ptr() as *const _ as *const c_char,
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LOOKUP_SET_ID as u16, self.set_id);
+
+            expr
+        }
+    }
+}
+
+/// Looks up the value in `src_reg` in a [`DataMap`] and loads the data of the matching element
+/// into `dreg`, `map` in `nft`. The rule stops matching if the value is not in the map.
+///
+/// [`DataMap`]: ../set/struct.DataMap.html
+pub struct DataLookup {
+    pub set_name: CString,
+    pub set_id: u32,
+    pub src_reg: Register,
+    pub dreg: Register,
+}
+
+impl DataLookup {
+    /// Returns a lookup of the value in `Register::Reg1` in `map`, loading the data into `dreg`.
+    pub fn new<K, V>(map: &DataMap<'_, K, V>, dreg: Register) -> Self {
+        DataLookup {
+            set_name: map.as_set().get_name().to_owned(),
+            set_id: map.as_set().get_id(),
+            src_reg: Register::Reg1,
+            dreg,
+        }
+    }
+}
+
+impl Expression for DataLookup {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"lookup\0" as *const _ as *const c_char
+            ));
+
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_LOOKUP_SREG as u16,
+                self.src_reg.to_raw(),
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LOOKUP_DREG as u16, self.dreg.to_raw());
+            sys::nftnl_expr_set_str(
+                expr,
+                sys::NFTNL_EXPR_LOOKUP_SET as u16,
+                self.set_name.as_ptr() as *const _ as *const c_char,
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_LOOKUP_SET_ID as u16, self.set_id);
 
             expr
         }
@@ -50,6 +167,9 @@ impl Expression for Lookup {
 
 #[macro_export]
 macro_rules! nft_expr_lookup {
+    (inverted $set:expr) => {
+        $crate::expr::Lookup::new_inverted($set)
+    };
     ($set:expr) => {
         $crate::expr::Lookup::new($set)
     };
@@ -1,12 +1,65 @@
-use super::{Expression, Rule};
+use super::{Expression, NatFlags, Register, Rule};
 use nftnl_sys as sys;
 use std::os::raw::c_char;
 
 /// Sets the source IP to that of the output interface.
-pub struct Masquerade;
+///
+/// With a `port_register` the source port is also translated, to the port in that register or
+/// to the range up to the port in `port_register_max`. Equivalent to `masquerade to :1024-2048`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Masquerade {
+    pub port_register: Option<Register>,
+    /// Register holding the last port of the port range. Ignored without a `port_register`.
+    pub port_register_max: Option<Register>,
+    pub flags: NatFlags,
+}
 
 impl Expression for Masquerade {
     fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
-        try_alloc!(unsafe { sys::nftnl_expr_alloc(b"masq\0" as *const _ as *const c_char) })
+        let expr =
+            try_alloc!(unsafe { sys::nftnl_expr_alloc(b"masq\0" as *const _ as *const c_char) });
+
+        unsafe {
+            if let Some(port_register) = self.port_register {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_MASQ_REG_PROTO_MIN as u16,
+                    port_register.to_raw(),
+                );
+                if let Some(port_register_max) = self.port_register_max {
+                    sys::nftnl_expr_set_u32(
+                        expr,
+                        sys::NFTNL_EXPR_MASQ_REG_PROTO_MAX as u16,
+                        port_register_max.to_raw(),
+                    );
+                }
+            }
+            if !self.flags.is_empty() {
+                sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_MASQ_FLAGS as u16, self.flags.bits());
+            }
+        }
+
+        expr
     }
 }
+
+#[macro_export(local_inner_macros)]
+macro_rules! nft_expr_masquerade {
+    (@flag random) => {
+        $crate::expr::NatFlags::PROTO_RANDOM
+    };
+    (@flag fully_random) => {
+        $crate::expr::NatFlags::PROTO_RANDOM_FULLY
+    };
+    (@flag persistent) => {
+        $crate::expr::NatFlags::PERSISTENT
+    };
+
+    ($($flag:ident)*) => {
+        $crate::expr::Masquerade {
+            port_register: None,
+            port_register_max: None,
+            flags: $crate::expr::NatFlags::empty() $(| nft_expr_masquerade!(@flag $flag))*,
+        }
+    };
+}
@@ -1,14 +1,66 @@
-use super::{Expression, Rule};
+use super::{Expression, Register, Rule, ToSlice};
 use nftnl_sys::{self as sys, libc};
-use std::os::raw::c_char;
+use std::{borrow::Cow, os::raw::c_char};
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_META_TIME_NS: u32 = 30;
+const NFT_META_TIME_DAY: u32 = 31;
+const NFT_META_TIME_HOUR: u32 = 32;
+
+// From linux/if_packet.h.
+const PACKET_HOST: u8 = 0;
+const PACKET_BROADCAST: u8 = 1;
+const PACKET_MULTICAST: u8 = 2;
+const PACKET_OTHERHOST: u8 = 3;
+
+/// The type of a packet, as loaded by [`Meta::PktType`].
+///
+/// [`Meta::PktType`]: enum.Meta.html#variant.PktType
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum PacketType {
+    /// Unicast to this host.
+    Host = PACKET_HOST,
+    /// Broadcast on the link layer.
+    Broadcast = PACKET_BROADCAST,
+    /// Multicast on the link layer.
+    Multicast = PACKET_MULTICAST,
+    /// Unicast to another host, seen because the interface is in promiscuous mode.
+    OtherHost = PACKET_OTHERHOST,
+}
+
+impl ToSlice for PacketType {
+    fn to_slice(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(vec![*self as u8])
+    }
+}
 
 /// A meta expression refers to meta data associated with a packet.
 #[non_exhaustive]
 pub enum Meta {
+    /// 32 bit length of the packet in bytes (skb->len), in host byte order. Like for
+    /// [`Hour`], ordering comparisons with [`Cmp`] only work as expected on big endian hosts.
+    ///
+    /// [`Hour`]: #variant.Hour
+    /// [`Cmp`]: struct.Cmp.html
+    Len,
     /// Packet ethertype protocol (skb->protocol), invalid in OUTPUT.
     Protocol,
-    /// Packet mark.
+    /// Packet mark. Loads the mark into the register, or stores the register value as the new
+    /// mark if `set` is `true`. The value to set has to be loaded into the register first, for
+    /// example with an [`Immediate`]. `meta mark set 0x42` is written as:
+    /// ```ignore
+    /// rule.add_expr(&nft_expr!(immediate u32 0x42));
+    /// rule.add_expr(&nft_expr!(meta mark set));
+    /// ```
+    ///
+    /// [`Immediate`]: struct.Immediate.html
     Mark { set: bool },
+    /// Packet priority (skb->priority), used by traffic control to classify the packet. Loads or,
+    /// if `set` is `true`, stores the 32 bit priority, like [`Mark`].
+    ///
+    /// [`Mark`]: #variant.Mark
+    Priority { set: bool },
     /// Packet input interface index (dev->ifindex).
     Iif,
     /// Packet output interface index (dev->ifindex).
@@ -21,9 +73,28 @@ pub enum Meta {
     IifType,
     /// Packet output interface type (dev->type).
     OifType,
-    /// Originating socket UID (fsuid).
+    /// 8 bit type of the packet, see [`PacketType`].
+    ///
+    /// [`PacketType`]: enum.PacketType.html
+    PktType,
+    /// 32 bit index of the CPU processing the packet.
+    Cpu,
+    /// 32 bit group of the input interface (dev->group), as set with
+    /// `ip link set dev eth0 group 2`.
+    IifGroup,
+    /// 32 bit group of the output interface (dev->group).
+    OifGroup,
+    /// The 8 bit trace flag of the packet. Loads the flag, or enables tracing for the packet
+    /// if `set` is `true` and the register holds `1u8`. Traced packets are reported to
+    /// `nft monitor trace` on every rule they pass.
+    NfTrace { set: bool },
+    /// Originating socket UID (fsuid). Only locally generated packets have a socket, so this
+    /// key is only useful in the `output` and `postrouting` hooks. Elsewhere the rule does not
+    /// match. This is not checked by the crate.
     SkUid,
-    /// Originating socket GID (fsgid).
+    /// Originating socket GID (fsgid). Has the same limitations as [`SkUid`].
+    ///
+    /// [`SkUid`]: #variant.SkUid
     SkGid,
     /// Netfilter protocol (Transport layer protocol).
     NfProto,
@@ -31,8 +102,47 @@ pub enum Meta {
     L4Proto,
     /// Socket control group (skb->sk->sk_classid).
     Cgroup,
-    /// A 32bit pseudo-random number
+    /// A 32bit pseudo-random number, evenly distributed over all values. Used to match a given
+    /// share of the packets, see the `filter-ethernet` example.
+    ///
+    /// The number is stored in host byte order, but [`Cmp`] compares register contents byte by
+    /// byte, as if they were big endian. The byte order of a random number does not matter for
+    /// its distribution, but the value it's compared against has to be converted with `to_be()`
+    /// for `<` and `>` to work as expected. So matching half of all packets is written as
+    /// `nft_expr!(cmp > (u32::MAX / 2).to_be())`. [`random_fraction_threshold`] computes the
+    /// converted value for other shares.
+    ///
+    /// [`Cmp`]: struct.Cmp.html
+    /// [`random_fraction_threshold`]: fn.random_fraction_threshold.html
     PRandom,
+    /// Name of the bridge the packet came in on. `IifName` is the name of the bridge port in this
+    /// case. Only valid in `bridge` family tables.
+    BrIifName,
+    /// Name of the bridge the packet goes out on. `OifName` is the name of the bridge port in
+    /// this case. Only valid in `bridge` family tables.
+    BrOifName,
+    /// The current time as a 64 bit number of nanoseconds since the Unix epoch, in host byte
+    /// order. Requires Linux 5.4 or newer, like [`Day`] and [`Hour`].
+    ///
+    /// [`Day`]: #variant.Day
+    /// [`Hour`]: #variant.Hour
+    Time,
+    /// The current day of the week as an 8 bit number, 0 being Sunday.
+    Day,
+    /// The current time of day as a 32 bit number of seconds since midnight, in host byte order.
+    ///
+    /// The day and the time of day are computed in the timezone of the kernel, which is usually
+    /// UTC. `nft` converts times given in local time before comparing, which has to be done
+    /// manually here.
+    ///
+    /// [`Cmp`] compares the register byte by byte, as if it was big endian. So on little endian
+    /// hosts only `==` and `!=` work as expected for this key and for [`Time`]. Ranges
+    /// need the value converted to big endian in the register first, which `nft` does with a
+    /// `byteorder` expression.
+    ///
+    /// [`Cmp`]: struct.Cmp.html
+    /// [`Time`]: #variant.Time
+    Hour,
 }
 
 impl Meta {
@@ -40,52 +150,135 @@ impl Meta {
     pub fn to_raw_key(&self) -> u32 {
         use Meta::*;
         match *self {
+            Len => libc::NFT_META_LEN as u32,
             Protocol => libc::NFT_META_PROTOCOL as u32,
             Mark { .. } => libc::NFT_META_MARK as u32,
+            Priority { .. } => libc::NFT_META_PRIORITY as u32,
             Iif => libc::NFT_META_IIF as u32,
             Oif => libc::NFT_META_OIF as u32,
             IifName => libc::NFT_META_IIFNAME as u32,
             OifName => libc::NFT_META_OIFNAME as u32,
             IifType => libc::NFT_META_IIFTYPE as u32,
             OifType => libc::NFT_META_OIFTYPE as u32,
+            PktType => libc::NFT_META_PKTTYPE as u32,
+            Cpu => libc::NFT_META_CPU as u32,
+            IifGroup => libc::NFT_META_IIFGROUP as u32,
+            OifGroup => libc::NFT_META_OIFGROUP as u32,
+            NfTrace { .. } => libc::NFT_META_NFTRACE as u32,
             SkUid => libc::NFT_META_SKUID as u32,
             SkGid => libc::NFT_META_SKGID as u32,
             NfProto => libc::NFT_META_NFPROTO as u32,
             L4Proto => libc::NFT_META_L4PROTO as u32,
             Cgroup => libc::NFT_META_CGROUP as u32,
             PRandom => libc::NFT_META_PRANDOM as u32,
+            BrIifName => libc::NFT_META_BRI_IIFNAME as u32,
+            BrOifName => libc::NFT_META_BRI_OIFNAME as u32,
+            Time => NFT_META_TIME_NS,
+            Day => NFT_META_TIME_DAY,
+            Hour => NFT_META_TIME_HOUR,
         }
     }
+
+    fn is_set(&self) -> bool {
+        matches!(
+            *self,
+            Meta::Mark { set: true } | Meta::Priority { set: true } | Meta::NfTrace { set: true }
+        )
+    }
+
+    /// Returns an expression using `reg` instead of the first register. See [`MetaWithReg`].
+    ///
+    /// [`MetaWithReg`]: struct.MetaWithReg.html
+    pub fn with_reg(self, reg: Register) -> MetaWithReg {
+        MetaWithReg { meta: self, reg }
+    }
+}
+
+/// A [`Meta`] expression using `reg` instead of the first register. `reg` is the register the
+/// value is loaded into, or for the variants with `set: true` the register the new value is read
+/// from.
+///
+/// Using several registers keeps earlier values around while loading new ones. This sets the
+/// mark of UDP packets to the index of their input interface, `meta l4proto udp meta mark set
+/// meta iif` in `nft`, without loading over the protocol in the first register:
+///
+/// ```ignore
+/// rule.add_expr(&nft_expr!(meta l4proto));
+/// rule.add_expr(&nft_expr!(cmp == libc::IPPROTO_UDP as u8));
+/// rule.add_expr(&nft_expr!(meta iif => Register::Reg2));
+/// rule.add_expr(&nft_expr!(meta mark set from Register::Reg2));
+/// ```
+///
+/// [`Meta`]: enum.Meta.html
+pub struct MetaWithReg {
+    pub meta: Meta,
+    pub reg: Register,
+}
+
+impl Expression for MetaWithReg {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        meta_expr(&self.meta, self.reg)
+    }
+}
+
+/// Returns the value to compare [`Meta::PRandom`] against with `cmp <` for the rule to match
+/// `percent` percent of the packets. The value is already converted to big endian, so it can be
+/// passed straight to [`Cmp`]:
+///
+/// ```ignore
+/// rule.add_expr(&nft_expr!(meta random));
+/// rule.add_expr(&nft_expr!(cmp < random_fraction_threshold(10)));
+/// ```
+///
+/// `100` returns `u32::MAX`, which matches all packets except the ones getting that exact random
+/// number, one in 2^32.
+///
+/// # Panics
+///
+/// Panics if `percent` is larger than 100.
+///
+/// [`Meta::PRandom`]: enum.Meta.html#variant.PRandom
+/// [`Cmp`]: struct.Cmp.html
+pub fn random_fraction_threshold(percent: u8) -> u32 {
+    assert!(percent <= 100, "percent must be at most 100");
+    let threshold = (u64::from(u32::MAX) + 1) * u64::from(percent) / 100;
+    (threshold.min(u64::from(u32::MAX)) as u32).to_be()
 }
 
 impl Expression for Meta {
     fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
-        unsafe {
-            let expr = try_alloc!(sys::nftnl_expr_alloc(
-                b"meta\0" as *const _ as *const c_char
-            ));
-
-            if let Meta::Mark { set: true } = self {
-                sys::nftnl_expr_set_u32(
-                    expr,
-                    sys::NFTNL_EXPR_META_SREG as u16,
-                    libc::NFT_REG_1 as u32,
-                );
-            } else {
-                sys::nftnl_expr_set_u32(
-                    expr,
-                    sys::NFTNL_EXPR_META_DREG as u16,
-                    libc::NFT_REG_1 as u32,
-                );
-            }
-            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_META_KEY as u16, self.to_raw_key());
-            expr
+        meta_expr(self, Register::Reg1)
+    }
+}
+
+fn meta_expr(meta: &Meta, reg: Register) -> *mut sys::nftnl_expr {
+    unsafe {
+        let expr = try_alloc!(sys::nftnl_expr_alloc(
+            b"meta\0" as *const _ as *const c_char
+        ));
+
+        if meta.is_set() {
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_META_SREG as u16, reg.to_raw());
+        } else {
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_META_DREG as u16, reg.to_raw());
         }
+        sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_META_KEY as u16, meta.to_raw_key());
+        expr
     }
 }
 
-#[macro_export]
+#[macro_export(local_inner_macros)]
 macro_rules! nft_expr_meta {
+    ($key:ident set from $reg:expr) => {
+        nft_expr_meta!($key set).with_reg($reg)
+    };
+    ($key:ident => $reg:expr) => {
+        nft_expr_meta!($key).with_reg($reg)
+    };
+
+    (length) => {
+        $crate::expr::Meta::Len
+    };
     (proto) => {
         $crate::expr::Meta::Protocol
     };
@@ -95,6 +288,12 @@ macro_rules! nft_expr_meta {
     (mark) => {
         $crate::expr::Meta::Mark { set: false }
     };
+    (priority set) => {
+        $crate::expr::Meta::Priority { set: true }
+    };
+    (priority) => {
+        $crate::expr::Meta::Priority { set: false }
+    };
     (iif) => {
         $crate::expr::Meta::Iif
     };
@@ -113,6 +312,24 @@ macro_rules! nft_expr_meta {
     (oiftype) => {
         $crate::expr::Meta::OifType
     };
+    (pkttype) => {
+        $crate::expr::Meta::PktType
+    };
+    (cpu) => {
+        $crate::expr::Meta::Cpu
+    };
+    (iifgroup) => {
+        $crate::expr::Meta::IifGroup
+    };
+    (oifgroup) => {
+        $crate::expr::Meta::OifGroup
+    };
+    (nftrace set) => {
+        $crate::expr::Meta::NfTrace { set: true }
+    };
+    (nftrace) => {
+        $crate::expr::Meta::NfTrace { set: false }
+    };
     (skuid) => {
         $crate::expr::Meta::SkUid
     };
@@ -131,4 +348,19 @@ macro_rules! nft_expr_meta {
     (random) => {
         $crate::expr::Meta::PRandom
     };
+    (ibrname) => {
+        $crate::expr::Meta::BrIifName
+    };
+    (obrname) => {
+        $crate::expr::Meta::BrOifName
+    };
+    (time) => {
+        $crate::expr::Meta::Time
+    };
+    (day) => {
+        $crate::expr::Meta::Day
+    };
+    (hour) => {
+        $crate::expr::Meta::Hour
+    };
 }
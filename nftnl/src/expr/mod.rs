@@ -4,6 +4,7 @@
 //! [`Rule`]: struct.Rule.html
 
 use super::rule::Rule;
+use core::fmt;
 use nftnl_sys::{self as sys, libc};
 
 /// Trait for every safe wrapper of an nftables expression.
@@ -13,6 +14,29 @@ pub trait Expression {
     fn to_expr(&self, rule: &Rule) -> *mut sys::nftnl_expr;
 }
 
+/// Error returned by [`Rule::try_add_expr`] when an expression could not be added to the rule.
+///
+/// [`Rule::try_add_expr`]: ../struct.Rule.html#method.try_add_expr
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ExprError {
+    /// [`Expression::to_expr`] returned a null pointer, because allocating the expression failed
+    /// or the expression is invalid.
+    ///
+    /// [`Expression::to_expr`]: trait.Expression.html#tymethod.to_expr
+    AllocationFailed,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ExprError::AllocationFailed => "Unable to create the expression".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
 /// A netfilter data register. The expressions store and read data to and from these
 /// when evaluating rule statements.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -36,15 +60,41 @@ pub use self::bitwise::*;
 mod cmp;
 pub use self::cmp::*;
 
+#[cfg(feature = "nftnl-1-1-1")]
+mod connlimit;
+#[cfg(feature = "nftnl-1-1-1")]
+pub use self::connlimit::*;
+
 mod counter;
 pub use self::counter::*;
 
 pub mod ct;
 pub use self::ct::*;
 
+mod dynset;
+pub use self::dynset::*;
+
+#[cfg(feature = "nftnl-1-0-7")]
+mod fib;
+#[cfg(feature = "nftnl-1-0-7")]
+pub use self::fib::*;
+
+#[cfg(feature = "nftnl-1-1-0")]
+mod flow_offload;
+#[cfg(feature = "nftnl-1-1-0")]
+pub use self::flow_offload::*;
+
+#[cfg(feature = "nftnl-1-0-8")]
+mod hash;
+#[cfg(feature = "nftnl-1-0-8")]
+pub use self::hash::*;
+
 mod immediate;
 pub use self::immediate::*;
 
+mod limit;
+pub use self::limit::*;
+
 mod lookup;
 pub use self::lookup::*;
 
@@ -57,9 +107,37 @@ pub use self::meta::*;
 mod nat;
 pub use self::nat::*;
 
+#[cfg(feature = "nftnl-1-0-7")]
+mod numgen;
+#[cfg(feature = "nftnl-1-0-7")]
+pub use self::numgen::*;
+
+#[cfg(feature = "nftnl-1-0-7")]
+mod objref;
+#[cfg(feature = "nftnl-1-0-7")]
+pub use self::objref::*;
+
+#[cfg(feature = "nftnl-1-1-2")]
+mod osf;
+#[cfg(feature = "nftnl-1-1-2")]
+pub use self::osf::*;
+
 mod payload;
 pub use self::payload::*;
 
+mod queue;
+pub use self::queue::*;
+
+#[cfg(feature = "nftnl-1-0-7")]
+mod range;
+#[cfg(feature = "nftnl-1-0-7")]
+pub use self::range::*;
+
+pub mod raw;
+
+mod redirect;
+pub use self::redirect::*;
+
 mod verdict;
 pub use self::verdict::*;
 
@@ -68,6 +146,9 @@ macro_rules! nft_expr {
     (bitwise mask $mask:expr,xor $xor:expr) => {
         nft_expr_bitwise!(mask $mask, xor $xor)
     };
+    (bitwise mask $mask:expr,xor $xor:expr,sreg $sreg:expr,dreg $dreg:expr) => {
+        nft_expr_bitwise!(mask $mask, xor $xor, sreg $sreg, dreg $dreg)
+    };
     (cmp $op:tt $data:expr) => {
         nft_expr_cmp!($op $data)
     };
@@ -80,17 +161,35 @@ macro_rules! nft_expr {
     (ct $key:ident) => {
         nft_expr_ct!($key)
     };
+    (ct $($tail:tt)+) => {
+        nft_expr_ct!($($tail)+)
+    };
+    (dynset $($tail:tt)+) => {
+        nft_expr_dynset!($($tail)+)
+    };
     (verdict $verdict:ident) => {
         nft_expr_verdict!($verdict)
     };
     (verdict $verdict:ident $chain:expr) => {
         nft_expr_verdict!($verdict $chain)
     };
+    (limit $($tail:tt)+) => {
+        nft_expr_limit!($($tail)+)
+    };
+    (lookup inverted $set:expr) => {
+        nft_expr_lookup!(inverted $set)
+    };
     (lookup $set:expr) => {
         nft_expr_lookup!($set)
     };
-    (masquerade) => {
-        $crate::expr::Masquerade
+    (masquerade $($flag:ident)*) => {
+        nft_expr_masquerade!($($flag)*)
+    };
+    (meta $expr:ident set from $reg:expr) => {
+        nft_expr_meta!($expr set from $reg)
+    };
+    (meta $expr:ident => $reg:expr) => {
+        nft_expr_meta!($expr => $reg)
     };
     (meta $expr:ident set) => {
         nft_expr_meta!($expr set)
@@ -98,12 +197,60 @@ macro_rules! nft_expr {
     (meta $expr:ident) => {
         nft_expr_meta!($expr)
     };
-    (payload $proto:ident $field:ident) => {
-        nft_expr_payload!($proto $field)
+    (numgen $($tail:tt)+) => {
+        nft_expr_numgen!($($tail)+)
+    };
+    (osf $($tail:tt)+) => {
+        nft_expr_osf!($($tail)+)
+    };
+    (payload write $($tail:tt)+) => {
+        nft_expr_payload!(write $($tail)+)
+    };
+    (payload $proto:ident $($field:ident)+ => $reg:expr) => {
+        nft_expr_payload!($proto $($field)+ => $reg)
+    };
+    (payload $proto:ident $($field:ident)+) => {
+        nft_expr_payload!($proto $($field)+)
     };
     (payload_raw $base:ident $offset:expr, $length:expr) => {
         nft_expr_payload!($base $offset, $length)
     };
+    (fib $($tail:tt)+) => {
+        nft_expr_fib!($($tail)+)
+    };
+    (flow add @$name:expr) => {
+        $crate::expr::FlowOffload {
+            flowtable_name: ::std::borrow::ToOwned::to_owned(
+                ::std::convert::AsRef::<::std::ffi::CStr>::as_ref(&$name),
+            ),
+        }
+    };
+    (hash $($tail:tt)+) => {
+        nft_expr_hash!($($tail)+)
+    };
+    (symhash $($tail:tt)+) => {
+        nft_expr_hash!(symhash $($tail)+)
+    };
+    (queue $($tail:tt)+) => {
+        nft_expr_queue!($($tail)+)
+    };
+    (range $($tail:tt)+) => {
+        nft_expr_range!($($tail)+)
+    };
+    (redirect) => {
+        $crate::expr::Redirect {
+            port_register: None,
+            port_register_max: None,
+            flags: $crate::expr::NatFlags::empty(),
+        }
+    };
+    (redirect to $port_register:expr) => {
+        $crate::expr::Redirect {
+            port_register: Some($port_register),
+            port_register_max: None,
+            flags: $crate::expr::NatFlags::empty(),
+        }
+    };
     (immediate $expr:ident $value:expr) => {
         nft_expr_immediate!($expr $value)
     };
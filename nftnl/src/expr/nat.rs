@@ -3,6 +3,25 @@ use crate::ProtoFamily;
 use nftnl_sys::{self as sys, libc};
 use std::os::raw::c_char;
 
+bitflags::bitflags! {
+    /// Flags for the [`Nat`] and [`Masquerade`] expressions, the `NF_NAT_RANGE_*` flags from
+    /// linux/netfilter/nf_nat.h.
+    ///
+    /// [`Nat`]: struct.Nat.html
+    /// [`Masquerade`]: struct.Masquerade.html
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct NatFlags: u32 {
+        /// Pick the port randomly instead of keeping the original one if it is free. `random`
+        /// in `nft`.
+        const PROTO_RANDOM = 0x04;
+        /// Give a client the same address for all its connections. `persistent` in `nft`.
+        const PERSISTENT = 0x08;
+        /// Pick the port fully randomly, with a random number generator instead of a hash.
+        /// `fully-random` in `nft`.
+        const PROTO_RANDOM_FULLY = 0x10;
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[repr(i32)]
 pub enum NatType {
@@ -18,7 +37,27 @@ pub struct Nat {
     pub nat_type: NatType,
     pub family: ProtoFamily,
     pub ip_register: Register,
+    /// Register holding the last address of the address range to translate to. When `None`
+    /// only the single address in `ip_register` is used.
+    pub ip_register_max: Option<Register>,
     pub port_register: Option<Register>,
+    /// Register holding the last port of the port range to translate to. When `None` only the
+    /// single port in `port_register` is used. Ignored without a `port_register`.
+    pub port_register_max: Option<Register>,
+    pub flags: NatFlags,
+}
+
+impl Nat {
+    /// Translates to an address pool instead of a single address. `min_reg` and `max_reg` must
+    /// hold the first and last address of the pool, for example loaded with [`Immediate`]
+    /// expressions. Equivalent to `snat to 10.0.0.1-10.0.0.255`.
+    ///
+    /// [`Immediate`]: struct.Immediate.html
+    pub fn with_addr_pool(mut self, min_reg: Register, max_reg: Register) -> Self {
+        self.ip_register = min_reg;
+        self.ip_register_max = Some(max_reg);
+        self
+    }
 }
 
 impl Expression for Nat {
@@ -34,12 +73,29 @@ impl Expression for Nat {
                 sys::NFTNL_EXPR_NAT_REG_ADDR_MIN as u16,
                 self.ip_register.to_raw(),
             );
+            if let Some(ip_register_max) = self.ip_register_max {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_NAT_REG_ADDR_MAX as u16,
+                    ip_register_max.to_raw(),
+                );
+            }
             if let Some(port_register) = self.port_register {
                 sys::nftnl_expr_set_u32(
                     expr,
                     sys::NFTNL_EXPR_NAT_REG_PROTO_MIN as u16,
                     port_register.to_raw(),
                 );
+                if let Some(port_register_max) = self.port_register_max {
+                    sys::nftnl_expr_set_u32(
+                        expr,
+                        sys::NFTNL_EXPR_NAT_REG_PROTO_MAX as u16,
+                        port_register_max.to_raw(),
+                    );
+                }
+            }
+            if !self.flags.is_empty() {
+                sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_NAT_FLAGS as u16, self.flags.bits());
             }
         }
 
@@ -0,0 +1,74 @@
+use super::{Expression, Rule};
+use nftnl_sys::{self as sys, libc};
+use std::os::raw::c_char;
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_NG_INCREMENTAL: u32 = 0;
+const NFT_NG_RANDOM: u32 = 1;
+
+/// How a [`Numgen`] expression generates its numbers.
+///
+/// [`Numgen`]: struct.Numgen.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum NumgenMode {
+    /// A counter incremented for every packet, for round robin.
+    Inc,
+    /// A pseudo-random number.
+    Random,
+}
+
+/// A number generator expression. Loads a number from `offset` to `offset + modulus - 1` into
+/// the register. Used for round robin or random load balancing, for example by comparing the
+/// number with [`Cmp`] or loading a NAT address from a map with it.
+///
+/// Requires the `nftnl-1-0-7` feature.
+///
+/// [`Cmp`]: struct.Cmp.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Numgen {
+    pub mode: NumgenMode,
+    pub modulus: u32,
+    pub offset: u32,
+}
+
+impl Expression for Numgen {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"numgen\0" as *const _ as *const c_char
+            ));
+
+            let mode = match self.mode {
+                NumgenMode::Inc => NFT_NG_INCREMENTAL,
+                NumgenMode::Random => NFT_NG_RANDOM,
+            };
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_NG_DREG as u16, libc::NFT_REG_1 as u32);
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_NG_TYPE as u16, mode);
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_NG_MODULUS as u16, self.modulus);
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_NG_OFFSET as u16, self.offset);
+
+            expr
+        }
+    }
+}
+
+#[macro_export(local_inner_macros)]
+macro_rules! nft_expr_numgen {
+    (@mode inc) => {
+        $crate::expr::NumgenMode::Inc
+    };
+    (@mode random) => {
+        $crate::expr::NumgenMode::Random
+    };
+
+    ($mode:ident mod $modulus:tt offset $offset:tt) => {
+        $crate::expr::Numgen {
+            mode: nft_expr_numgen!(@mode $mode),
+            modulus: $modulus,
+            offset: $offset,
+        }
+    };
+    ($mode:ident mod $modulus:tt) => {
+        nft_expr_numgen!($mode mod $modulus offset 0)
+    };
+}
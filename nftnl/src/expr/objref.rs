@@ -0,0 +1,153 @@
+use super::{Expression, Rule};
+#[cfg(feature = "nftnl-1-0-8")]
+use crate::object::{CtHelper, NamedLimit, NFT_OBJECT_CT_HELPER, NFT_OBJECT_LIMIT};
+#[cfg(feature = "nftnl-1-1-2")]
+use crate::object::{CtTimeout, NFT_OBJECT_CT_TIMEOUT};
+use crate::object::{NamedCounter, NamedQuota, NFT_OBJECT_COUNTER, NFT_OBJECT_QUOTA};
+use nftnl_sys as sys;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+};
+
+/// Counts the packet in a [`NamedCounter`], `counter name "name"` in `nft`.
+///
+/// Requires the `nftnl-1-0-7` feature.
+///
+/// [`NamedCounter`]: ../object/struct.NamedCounter.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CounterRef {
+    pub name: CString,
+}
+
+impl CounterRef {
+    pub fn new(counter: &NamedCounter<'_>) -> Self {
+        CounterRef {
+            name: counter.name.clone(),
+        }
+    }
+}
+
+impl Expression for CounterRef {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        objref_expr(NFT_OBJECT_COUNTER, &self.name)
+    }
+}
+
+/// Applies a [`NamedQuota`] to the packet, `quota name "name"` in `nft`.
+///
+/// Requires the `nftnl-1-0-7` feature.
+///
+/// [`NamedQuota`]: ../object/struct.NamedQuota.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct QuotaRef {
+    pub name: CString,
+}
+
+impl QuotaRef {
+    pub fn new(quota: &NamedQuota<'_>) -> Self {
+        QuotaRef {
+            name: quota.name.clone(),
+        }
+    }
+}
+
+impl Expression for QuotaRef {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        objref_expr(NFT_OBJECT_QUOTA, &self.name)
+    }
+}
+
+/// Applies a [`NamedLimit`] to the packet, `limit name "name"` in `nft`.
+///
+/// Requires the `nftnl-1-0-8` feature.
+///
+/// [`NamedLimit`]: ../object/struct.NamedLimit.html
+#[cfg(feature = "nftnl-1-0-8")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LimitRef {
+    pub name: CString,
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+impl LimitRef {
+    pub fn new(limit: &NamedLimit<'_>) -> Self {
+        LimitRef {
+            name: limit.name.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+impl Expression for LimitRef {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        objref_expr(NFT_OBJECT_LIMIT, &self.name)
+    }
+}
+
+/// Assigns a [`CtHelper`] to the connection of the packet, `ct helper set "name"` in `nft`.
+///
+/// Requires the `nftnl-1-0-8` feature.
+///
+/// [`CtHelper`]: ../object/struct.CtHelper.html
+#[cfg(feature = "nftnl-1-0-8")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CtHelperRef {
+    pub name: CString,
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+impl CtHelperRef {
+    pub fn new(helper: &CtHelper<'_>) -> Self {
+        CtHelperRef {
+            name: helper.name.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+impl Expression for CtHelperRef {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        objref_expr(NFT_OBJECT_CT_HELPER, &self.name)
+    }
+}
+
+/// Assigns a [`CtTimeout`] policy to the connection of the packet, `ct timeout set "name"` in
+/// `nft`.
+///
+/// Requires the `nftnl-1-1-2` feature.
+///
+/// [`CtTimeout`]: ../object/struct.CtTimeout.html
+#[cfg(feature = "nftnl-1-1-2")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CtTimeoutRef {
+    pub name: CString,
+}
+
+#[cfg(feature = "nftnl-1-1-2")]
+impl CtTimeoutRef {
+    pub fn new(timeout: &CtTimeout<'_>) -> Self {
+        CtTimeoutRef {
+            name: timeout.name.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "nftnl-1-1-2")]
+impl Expression for CtTimeoutRef {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        objref_expr(NFT_OBJECT_CT_TIMEOUT, &self.name)
+    }
+}
+
+/// Returns an `objref` expression referencing the object of type `obj_type` named `name`.
+fn objref_expr(obj_type: u32, name: &CStr) -> *mut sys::nftnl_expr {
+    unsafe {
+        let expr = try_alloc!(sys::nftnl_expr_alloc(
+            b"objref\0" as *const _ as *const c_char
+        ));
+        sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_OBJREF_IMM_TYPE as u16, obj_type);
+        sys::nftnl_expr_set_str(expr, sys::NFTNL_EXPR_OBJREF_IMM_NAME as u16, name.as_ptr());
+        expr
+    }
+}
@@ -0,0 +1,63 @@
+use super::{Expression, Register, Rule};
+use nftnl_sys as sys;
+use std::os::raw::c_char;
+
+/// How an [`Osf`] expression checks the TTL of the packet against the fingerprint.
+///
+/// [`Osf`]: struct.Osf.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum OsfTtl {
+    /// The TTL must match the fingerprint exactly. Only works for hosts on the local network.
+    DontSkip = 0,
+    /// The TTL may be lower than the one of the fingerprint. `ttl loose` in `nft`.
+    Loose = 1,
+    /// The TTL is not checked. `ttl skip` in `nft`.
+    Skip = 2,
+}
+
+/// Passive operating system fingerprinting of TCP SYN packets. Loads the name of the detected
+/// operating system, such as `Linux`, into `dreg`, or `unknown`. The name takes up 16 bytes, so
+/// compare it with a nul terminated string: `nft_expr!(cmp == "Linux\0")`.
+///
+/// The fingerprints must first be loaded into the kernel with `nfnl_osf` from iptables.
+///
+/// Requires the `nftnl-1-1-2` feature.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Osf {
+    pub ttl: OsfTtl,
+    pub dreg: Register,
+}
+
+impl Expression for Osf {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(b"osf\0" as *const _ as *const c_char));
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_OSF_DREG as u16, self.dreg.to_raw());
+            sys::nftnl_expr_set_u8(expr, sys::NFTNL_EXPR_OSF_TTL as u16, self.ttl as u8);
+            expr
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! nft_expr_osf {
+    (name) => {
+        $crate::expr::Osf {
+            ttl: $crate::expr::OsfTtl::DontSkip,
+            dreg: $crate::expr::Register::Reg1,
+        }
+    };
+    (ttl loose name) => {
+        $crate::expr::Osf {
+            ttl: $crate::expr::OsfTtl::Loose,
+            dreg: $crate::expr::Register::Reg1,
+        }
+    };
+    (ttl skip name) => {
+        $crate::expr::Osf {
+            ttl: $crate::expr::OsfTtl::Skip,
+            dreg: $crate::expr::Register::Reg1,
+        }
+    };
+}
@@ -1,12 +1,42 @@
-use super::{Expression, Rule};
+use super::{Expression, Register, Rule, ToSlice};
 use nftnl_sys::{self as sys, libc};
-use std::os::raw::c_char;
+use std::{borrow::Cow, os::raw::c_char};
 
 trait HeaderField {
     fn offset(&self) -> u32;
     fn len(&self) -> u32;
 }
 
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_PAYLOAD_INNER_HEADER: u32 = 3;
+const NFT_PAYLOAD_CSUM_SCTP: u32 = 2;
+
+/// The header a payload offset is relative to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PayloadBase {
+    /// The link layer header, such as the ethernet header.
+    LinkLayer,
+    /// The network header, such as the IPv4 or IPv6 header.
+    Network,
+    /// The transport header, such as the TCP or UDP header.
+    Transport,
+    /// The tunnel header of an encapsulated packet, such as the VXLAN header. Only valid in
+    /// payload expressions nested in an `inner` expression, which requires Linux 6.2 or newer.
+    Inner,
+}
+
+impl PayloadBase {
+    /// Returns the corresponding `NFT_PAYLOAD_*` constant for this base.
+    pub fn to_raw(self) -> u32 {
+        match self {
+            PayloadBase::LinkLayer => libc::NFT_PAYLOAD_LL_HEADER as u32,
+            PayloadBase::Network => libc::NFT_PAYLOAD_NETWORK_HEADER as u32,
+            PayloadBase::Transport => libc::NFT_PAYLOAD_TRANSPORT_HEADER as u32,
+            PayloadBase::Inner => NFT_PAYLOAD_INNER_HEADER,
+        }
+    }
+}
+
 /// Payload expressions refer to data from the packet's payload.
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Payload {
@@ -16,11 +46,12 @@ pub enum Payload {
 }
 
 impl Payload {
-    fn base(self) -> u32 {
+    /// Returns the header the field of this payload expression is in.
+    pub fn base(self) -> PayloadBase {
         match self {
-            Payload::LinkLayer(_) => libc::NFT_PAYLOAD_LL_HEADER as u32,
-            Payload::Network(_) => libc::NFT_PAYLOAD_NETWORK_HEADER as u32,
-            Payload::Transport(_) => libc::NFT_PAYLOAD_TRANSPORT_HEADER as u32,
+            Payload::LinkLayer(_) => PayloadBase::LinkLayer,
+            Payload::Network(_) => PayloadBase::Network,
+            Payload::Transport(_) => PayloadBase::Transport,
         }
     }
 }
@@ -45,34 +76,195 @@ impl HeaderField for Payload {
     }
 }
 
+impl Payload {
+    /// Returns an expression loading this field into `dest_reg` instead of the first register.
+    pub fn with_dest_reg(self, dest_reg: Register) -> PayloadLoad {
+        PayloadLoad {
+            field: self,
+            dest_reg,
+        }
+    }
+}
+
 impl Expression for Payload {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        payload_expr(self.base(), self.offset(), self.len(), Register::Reg1)
+    }
+}
+
+/// Loads `field` into `dest_reg`. A [`Payload`] expression always loads into the first
+/// register, this allows loading several fields and comparing them to each other or using them
+/// together, for example as a concatenated set key.
+///
+/// [`Payload`]: enum.Payload.html
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct PayloadLoad {
+    pub field: Payload,
+    pub dest_reg: Register,
+}
+
+impl Expression for PayloadLoad {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        payload_expr(
+            self.field.base(),
+            self.field.offset(),
+            self.field.len(),
+            self.dest_reg,
+        )
+    }
+}
+
+/// A payload expression loading `len` bytes at `offset` bytes into the `base` header. For
+/// fields [`Payload`] has no variant for.
+///
+/// [`Payload`]: enum.Payload.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PayloadRaw {
+    pub base: PayloadBase,
+    pub offset: u32,
+    pub len: u32,
+}
+
+impl Expression for PayloadRaw {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        payload_expr(self.base, self.offset, self.len, Register::Reg1)
+    }
+}
+
+/// How the kernel updates the checksum of the packet after a [`PayloadWrite`].
+///
+/// [`PayloadWrite`]: struct.PayloadWrite.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PayloadCsumType {
+    /// The checksum is not updated.
+    None,
+    /// The internet checksum, as used by IPv4, TCP, UDP and ICMP, is updated incrementally.
+    Inet,
+    /// The CRC32c checksum of SCTP is recomputed. Requires Linux 5.6 or newer.
+    Sctp,
+}
+
+impl PayloadCsumType {
+    /// Returns the corresponding `NFT_PAYLOAD_CSUM_*` constant for this checksum type.
+    pub fn to_raw(self) -> u32 {
+        match self {
+            PayloadCsumType::None => libc::NFT_PAYLOAD_CSUM_NONE as u32,
+            PayloadCsumType::Inet => libc::NFT_PAYLOAD_CSUM_INET as u32,
+            PayloadCsumType::Sctp => NFT_PAYLOAD_CSUM_SCTP,
+        }
+    }
+}
+
+/// Writes the value in `src_reg` to `field` in the packet, `@nh,64,8 set ...` or
+/// `ip ttl set ...` in `nft`. The checksum at `csum_offset` bytes into the header of the field
+/// is then updated as given by `csum_type`.
+///
+/// Only the checksum of the header the field is in is updated. Rewriting an IPv4 address does
+/// not fix the TCP or UDP checksum, which covers the address through the pseudo header.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct PayloadWrite {
+    pub field: Payload,
+    pub src_reg: Register,
+    pub csum_type: PayloadCsumType,
+    pub csum_offset: u32,
+}
+
+impl PayloadWrite {
+    /// Returns a write of `src_reg` to `field`, updating the checksum of the IPv4, TCP, UDP,
+    /// ICMP, ICMPv6 or SCTP header the field is in. Fields in other headers are written without
+    /// a checksum update.
+    pub fn new(field: Payload, src_reg: Register) -> Self {
+        let (csum_type, csum_offset) = match field {
+            Payload::Network(NetworkHeaderField::Ipv4(_)) => (PayloadCsumType::Inet, 10),
+            Payload::Transport(TransportHeaderField::Tcp(_)) => (PayloadCsumType::Inet, 16),
+            Payload::Transport(TransportHeaderField::Udp(_)) => (PayloadCsumType::Inet, 6),
+            Payload::Transport(TransportHeaderField::Icmp(_))
+            | Payload::Transport(TransportHeaderField::Icmpv6(_)) => (PayloadCsumType::Inet, 2),
+            Payload::Transport(TransportHeaderField::Sctp(_)) => (PayloadCsumType::Sctp, 8),
+            _ => (PayloadCsumType::None, 0),
+        };
+        PayloadWrite {
+            field,
+            src_reg,
+            csum_type,
+            csum_offset,
+        }
+    }
+}
+
+impl Expression for PayloadWrite {
     fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
         unsafe {
             let expr = try_alloc!(sys::nftnl_expr_alloc(
                 b"payload\0" as *const _ as *const c_char
             ));
 
-            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_BASE as u16, self.base());
-            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_OFFSET as u16, self.offset());
-            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_LEN as u16, self.len());
             sys::nftnl_expr_set_u32(
                 expr,
-                sys::NFTNL_EXPR_PAYLOAD_DREG as u16,
-                libc::NFT_REG_1 as u32,
+                sys::NFTNL_EXPR_PAYLOAD_BASE as u16,
+                self.field.base().to_raw(),
+            );
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_PAYLOAD_OFFSET as u16,
+                self.field.offset(),
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_LEN as u16, self.field.len());
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_PAYLOAD_SREG as u16,
+                self.src_reg.to_raw(),
             );
+            if self.csum_type != PayloadCsumType::None {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_PAYLOAD_CSUM_TYPE as u16,
+                    self.csum_type.to_raw(),
+                );
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_PAYLOAD_CSUM_OFFSET as u16,
+                    self.csum_offset,
+                );
+            }
 
             expr
         }
     }
 }
 
+fn payload_expr(
+    base: PayloadBase,
+    offset: u32,
+    len: u32,
+    dest_reg: Register,
+) -> *mut sys::nftnl_expr {
+    unsafe {
+        let expr = try_alloc!(sys::nftnl_expr_alloc(
+            b"payload\0" as *const _ as *const c_char
+        ));
+
+        sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_BASE as u16, base.to_raw());
+        sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_OFFSET as u16, offset);
+        sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_LEN as u16, len);
+        sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_PAYLOAD_DREG as u16, dest_reg.to_raw());
+
+        expr
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum LLHeaderField {
     Daddr,
     Saddr,
     EtherType,
-    Raw { offset_bits: u32, length_bits: u32 },
+    /// A field of the 802.1Q VLAN tag following the ethernet header.
+    Vlan(VlanHeaderField),
+    Raw {
+        offset_bits: u32,
+        length_bits: u32,
+    },
 }
 
 impl HeaderField for LLHeaderField {
@@ -82,6 +274,7 @@ impl HeaderField for LLHeaderField {
             Daddr => 0,
             Saddr => 6,
             EtherType => 12,
+            Vlan(ref f) => f.offset(),
             Raw { offset_bits, .. } => offset_bits,
         }
     }
@@ -92,15 +285,61 @@ impl HeaderField for LLHeaderField {
             Daddr => 6,
             Saddr => 6,
             EtherType => 2,
+            Vlan(ref f) => f.len(),
             Raw { length_bits, .. } => length_bits,
         }
     }
 }
 
+/// The fields of an 802.1Q VLAN tag. The kernel usually strips the tag from the packet and
+/// keeps it as metadata, but payload expressions still see it as if it was in the packet.
+///
+/// The priority, the drop eligible indicator and the VLAN ID share the two tag control bytes,
+/// so they have to be isolated with a [`Bitwise`] mask before comparing.
+///
+/// [`Bitwise`]: struct.Bitwise.html
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum VlanHeaderField {
+    /// The byte holding the three bit priority code point in its upper bits. Mask it with
+    /// `0xe0u8`.
+    Pcp,
+    /// The byte holding the drop eligible indicator. Mask it with `0x10u8`.
+    Dei,
+    /// The two bytes holding the 12 bit VLAN ID in their lower bits. Mask them with
+    /// `0x0fffu16.to_be()`.
+    Vid,
+    /// The ethertype of the encapsulated frame.
+    EtherType,
+}
+
+impl HeaderField for VlanHeaderField {
+    fn offset(&self) -> u32 {
+        use self::VlanHeaderField::*;
+        match *self {
+            Pcp => 14,
+            Dei => 14,
+            Vid => 14,
+            EtherType => 16,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::VlanHeaderField::*;
+        match *self {
+            Pcp => 1,
+            Dei => 1,
+            Vid => 2,
+            EtherType => 2,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum NetworkHeaderField {
     Ipv4(Ipv4HeaderField),
     Ipv6(Ipv6HeaderField),
+    Arp(ArpHeaderField),
     Raw { offset_bits: u32, length_bits: u32 },
 }
 
@@ -110,6 +349,7 @@ impl HeaderField for NetworkHeaderField {
         match *self {
             Ipv4(ref f) => f.offset(),
             Ipv6(ref f) => f.offset(),
+            Arp(ref f) => f.offset(),
             Raw { offset_bits, .. } => offset_bits,
         }
     }
@@ -119,6 +359,7 @@ impl HeaderField for NetworkHeaderField {
         match *self {
             Ipv4(ref f) => f.len(),
             Ipv6(ref f) => f.len(),
+            Arp(ref f) => f.len(),
             Raw { length_bits, .. } => length_bits,
         }
     }
@@ -127,16 +368,60 @@ impl HeaderField for NetworkHeaderField {
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum Ipv4HeaderField {
+    /// The type of service byte. It holds the DSCP in its upper six bits and the ECN in its
+    /// lower two bits, see [`DSCP_MASK`] and [`ECN_MASK`]. Matching DSCP class CS5, `ip dscp cs5`
+    /// in `nft`, is written as:
+    /// ```ignore
+    /// rule.add_expr(&nft_expr!(payload ipv4 tos));
+    /// rule.add_expr(&nft_expr!(bitwise mask Ipv4HeaderField::DSCP_MASK, xor 0u8));
+    /// rule.add_expr(&nft_expr!(cmp == 40u8 << 2));
+    /// ```
+    ///
+    /// [`DSCP_MASK`]: #associatedconstant.DSCP_MASK
+    /// [`ECN_MASK`]: #associatedconstant.ECN_MASK
+    Tos,
+    /// The byte holding the version in its upper four bits and the header length, in 32 bit
+    /// words, in its lower four bits.
+    Ihl,
+    TotalLen,
+    Id,
+    /// The flags in the upper three bits and the fragment offset in the lower 13 bits, in
+    /// network byte order. The flags are reserved (`0x8000`), don't fragment (`0x4000`) and more
+    /// fragments (`0x2000`). Matching all fragments, i.e. packets with the more fragments flag
+    /// or a non-zero offset, is written as:
+    /// ```ignore
+    /// rule.add_expr(&nft_expr!(payload ipv4 fragoff));
+    /// rule.add_expr(&nft_expr!(bitwise mask 0x3fffu16.to_be(), xor 0u16));
+    /// rule.add_expr(&nft_expr!(cmp != 0u16));
+    /// ```
+    FragOffset,
     Ttl,
     Protocol,
     Saddr,
     Daddr,
 }
 
+impl Ipv4HeaderField {
+    /// Mask of the DSCP bits in the [`Tos`] byte. The DSCP is shifted two bits to the left in
+    /// the byte, so class CS5 (40) is `40 << 2` after masking.
+    ///
+    /// [`Tos`]: #variant.Tos
+    pub const DSCP_MASK: u8 = 0xfc;
+    /// Mask of the ECN bits in the [`Tos`] byte.
+    ///
+    /// [`Tos`]: #variant.Tos
+    pub const ECN_MASK: u8 = 0x03;
+}
+
 impl HeaderField for Ipv4HeaderField {
     fn offset(&self) -> u32 {
         use self::Ipv4HeaderField::*;
         match *self {
+            Ihl => 0,
+            Tos => 1,
+            TotalLen => 2,
+            Id => 4,
+            FragOffset => 6,
             Ttl => 8,
             Protocol => 9,
             Saddr => 12,
@@ -147,6 +432,11 @@ impl HeaderField for Ipv4HeaderField {
     fn len(&self) -> u32 {
         use self::Ipv4HeaderField::*;
         match *self {
+            Ihl => 1,
+            Tos => 1,
+            TotalLen => 2,
+            Id => 2,
+            FragOffset => 2,
             Ttl => 1,
             Protocol => 1,
             Saddr => 4,
@@ -158,6 +448,14 @@ impl HeaderField for Ipv4HeaderField {
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum Ipv6HeaderField {
+    /// The first two bytes of the header. The traffic class is in the middle, after the four
+    /// bit version and before the upper four bits of the flow label, so mask the bytes with
+    /// `0x0ff0u16.to_be()` to isolate it.
+    TrafficClass,
+    /// Three bytes holding the 20 bit flow label in their lower bits. Mask the bytes with
+    /// `[0x0f, 0xff, 0xff]` to isolate it.
+    FlowLabel,
+    PayloadLen,
     NextHeader,
     HopLimit,
     Saddr,
@@ -168,6 +466,9 @@ impl HeaderField for Ipv6HeaderField {
     fn offset(&self) -> u32 {
         use self::Ipv6HeaderField::*;
         match *self {
+            TrafficClass => 0,
+            FlowLabel => 1,
+            PayloadLen => 4,
             NextHeader => 6,
             HopLimit => 7,
             Saddr => 8,
@@ -178,6 +479,9 @@ impl HeaderField for Ipv6HeaderField {
     fn len(&self) -> u32 {
         use self::Ipv6HeaderField::*;
         match *self {
+            TrafficClass => 2,
+            FlowLabel => 3,
+            PayloadLen => 2,
             NextHeader => 1,
             HopLimit => 1,
             Saddr => 16,
@@ -186,12 +490,62 @@ impl HeaderField for Ipv6HeaderField {
     }
 }
 
+/// The fields of an ARP packet for IPv4 over ethernet, the only kind the `arp` family handles.
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ArpHeaderField {
+    HwType,
+    ProtoType,
+    HwLen,
+    ProtoLen,
+    /// The operation, in network byte order. `1` for requests and `2` for replies.
+    Operation,
+    SenderHwAddr,
+    SenderProtoAddr,
+    TargetHwAddr,
+    TargetProtoAddr,
+}
+
+impl HeaderField for ArpHeaderField {
+    fn offset(&self) -> u32 {
+        use self::ArpHeaderField::*;
+        match *self {
+            HwType => 0,
+            ProtoType => 2,
+            HwLen => 4,
+            ProtoLen => 5,
+            Operation => 6,
+            SenderHwAddr => 8,
+            SenderProtoAddr => 14,
+            TargetHwAddr => 18,
+            TargetProtoAddr => 24,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::ArpHeaderField::*;
+        match *self {
+            HwType => 2,
+            ProtoType => 2,
+            HwLen => 1,
+            ProtoLen => 1,
+            Operation => 2,
+            SenderHwAddr => 6,
+            SenderProtoAddr => 4,
+            TargetHwAddr => 6,
+            TargetProtoAddr => 4,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum TransportHeaderField {
     Tcp(TcpHeaderField),
     Udp(UdpHeaderField),
+    Icmp(IcmpHeaderField),
     Icmpv6(Icmpv6HeaderField),
+    Sctp(SctpHeaderField),
     Raw { offset_bits: u32, length_bits: u32 },
 }
 
@@ -201,7 +555,9 @@ impl HeaderField for TransportHeaderField {
         match *self {
             Tcp(ref f) => f.offset(),
             Udp(ref f) => f.offset(),
+            Icmp(ref f) => f.offset(),
             Icmpv6(ref f) => f.offset(),
+            Sctp(ref f) => f.offset(),
             Raw { offset_bits, .. } => offset_bits,
         }
     }
@@ -211,7 +567,9 @@ impl HeaderField for TransportHeaderField {
         match *self {
             Tcp(ref f) => f.len(),
             Udp(ref f) => f.len(),
+            Icmp(ref f) => f.len(),
             Icmpv6(ref f) => f.len(),
+            Sctp(ref f) => f.len(),
             Raw { length_bits, .. } => length_bits,
         }
     }
@@ -222,6 +580,20 @@ impl HeaderField for TransportHeaderField {
 pub enum TcpHeaderField {
     Sport,
     Dport,
+    Seq,
+    Ack,
+    /// The byte holding the data offset in its upper four bits. Mask it with a [`Bitwise`]
+    /// expression to compare only the offset.
+    ///
+    /// [`Bitwise`]: struct.Bitwise.html
+    DataOffset,
+    /// The flags byte, see [`TcpFlags`].
+    ///
+    /// [`TcpFlags`]: struct.TcpFlags.html
+    Flags,
+    Window,
+    Checksum,
+    UrgPtr,
 }
 
 impl HeaderField for TcpHeaderField {
@@ -230,6 +602,13 @@ impl HeaderField for TcpHeaderField {
         match *self {
             Sport => 0,
             Dport => 2,
+            Seq => 4,
+            Ack => 8,
+            DataOffset => 12,
+            Flags => 13,
+            Window => 14,
+            Checksum => 16,
+            UrgPtr => 18,
         }
     }
 
@@ -238,10 +617,42 @@ impl HeaderField for TcpHeaderField {
         match *self {
             Sport => 2,
             Dport => 2,
+            Seq => 4,
+            Ack => 4,
+            DataOffset => 1,
+            Flags => 1,
+            Window => 2,
+            Checksum => 2,
+            UrgPtr => 2,
         }
     }
 }
 
+bitflags::bitflags! {
+    /// The flags in the TCP header, as loaded by `nft_expr!(payload tcp flags)`. Comparing with
+    /// `==` matches packets with exactly these flags set. To match packets having some flag set,
+    /// no matter the others, mask the flags with a [`Bitwise`] expression first.
+    ///
+    /// [`Bitwise`]: struct.Bitwise.html
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct TcpFlags: u8 {
+        const FIN = 0x01;
+        const SYN = 0x02;
+        const RST = 0x04;
+        const PSH = 0x08;
+        const ACK = 0x10;
+        const URG = 0x20;
+        const ECE = 0x40;
+        const CWR = 0x80;
+    }
+}
+
+impl ToSlice for TcpFlags {
+    fn to_slice(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(vec![self.bits()])
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum UdpHeaderField {
@@ -270,6 +681,42 @@ impl HeaderField for UdpHeaderField {
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IcmpHeaderField {
+    Type,
+    Code,
+    Checksum,
+    /// Identifier of echo requests and replies.
+    Id,
+    /// Sequence number of echo requests and replies.
+    Sequence,
+}
+
+impl HeaderField for IcmpHeaderField {
+    fn offset(&self) -> u32 {
+        use self::IcmpHeaderField::*;
+        match *self {
+            Type => 0,
+            Code => 1,
+            Checksum => 2,
+            Id => 4,
+            Sequence => 6,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::IcmpHeaderField::*;
+        match *self {
+            Type => 1,
+            Code => 1,
+            Checksum => 2,
+            Id => 2,
+            Sequence => 2,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum Icmpv6HeaderField {
@@ -298,8 +745,68 @@ impl HeaderField for Icmpv6HeaderField {
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SctpHeaderField {
+    Sport,
+    Dport,
+    /// The verification tag, identifying the association the packet belongs to.
+    VerificationTag,
+    Checksum,
+}
+
+impl HeaderField for SctpHeaderField {
+    fn offset(&self) -> u32 {
+        use self::SctpHeaderField::*;
+        match *self {
+            Sport => 0,
+            Dport => 2,
+            VerificationTag => 4,
+            Checksum => 8,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::SctpHeaderField::*;
+        match *self {
+            Sport => 2,
+            Dport => 2,
+            VerificationTag => 4,
+            Checksum => 4,
+        }
+    }
+}
+
 #[macro_export(local_inner_macros)]
 macro_rules! nft_expr_payload {
+    (@vlan_field pcp) => {
+        $crate::expr::VlanHeaderField::Pcp
+    };
+    (@vlan_field dei) => {
+        $crate::expr::VlanHeaderField::Dei
+    };
+    (@vlan_field vid) => {
+        $crate::expr::VlanHeaderField::Vid
+    };
+    (@vlan_field type) => {
+        $crate::expr::VlanHeaderField::EtherType
+    };
+
+    (@ipv4_field ihl) => {
+        $crate::expr::Ipv4HeaderField::Ihl
+    };
+    (@ipv4_field tos) => {
+        $crate::expr::Ipv4HeaderField::Tos
+    };
+    (@ipv4_field totallen) => {
+        $crate::expr::Ipv4HeaderField::TotalLen
+    };
+    (@ipv4_field id) => {
+        $crate::expr::Ipv4HeaderField::Id
+    };
+    (@ipv4_field fragoff) => {
+        $crate::expr::Ipv4HeaderField::FragOffset
+    };
     (@ipv4_field ttl) => {
         $crate::expr::Ipv4HeaderField::Ttl
     };
@@ -313,6 +820,15 @@ macro_rules! nft_expr_payload {
         $crate::expr::Ipv4HeaderField::Daddr
     };
 
+    (@ipv6_field trafficclass) => {
+        $crate::expr::Ipv6HeaderField::TrafficClass
+    };
+    (@ipv6_field flowlabel) => {
+        $crate::expr::Ipv6HeaderField::FlowLabel
+    };
+    (@ipv6_field payloadlen) => {
+        $crate::expr::Ipv6HeaderField::PayloadLen
+    };
     (@ipv6_field nextheader) => {
         $crate::expr::Ipv6HeaderField::NextHeader
     };
@@ -326,12 +842,61 @@ macro_rules! nft_expr_payload {
         $crate::expr::Ipv6HeaderField::Daddr
     };
 
+    (@arp_field htype) => {
+        $crate::expr::ArpHeaderField::HwType
+    };
+    (@arp_field ptype) => {
+        $crate::expr::ArpHeaderField::ProtoType
+    };
+    (@arp_field hlen) => {
+        $crate::expr::ArpHeaderField::HwLen
+    };
+    (@arp_field plen) => {
+        $crate::expr::ArpHeaderField::ProtoLen
+    };
+    (@arp_field operation) => {
+        $crate::expr::ArpHeaderField::Operation
+    };
+    (@arp_field saddr ether) => {
+        $crate::expr::ArpHeaderField::SenderHwAddr
+    };
+    (@arp_field saddr ip) => {
+        $crate::expr::ArpHeaderField::SenderProtoAddr
+    };
+    (@arp_field daddr ether) => {
+        $crate::expr::ArpHeaderField::TargetHwAddr
+    };
+    (@arp_field daddr ip) => {
+        $crate::expr::ArpHeaderField::TargetProtoAddr
+    };
+
     (@tcp_field sport) => {
         $crate::expr::TcpHeaderField::Sport
     };
     (@tcp_field dport) => {
         $crate::expr::TcpHeaderField::Dport
     };
+    (@tcp_field seq) => {
+        $crate::expr::TcpHeaderField::Seq
+    };
+    (@tcp_field ack) => {
+        $crate::expr::TcpHeaderField::Ack
+    };
+    (@tcp_field doff) => {
+        $crate::expr::TcpHeaderField::DataOffset
+    };
+    (@tcp_field flags) => {
+        $crate::expr::TcpHeaderField::Flags
+    };
+    (@tcp_field window) => {
+        $crate::expr::TcpHeaderField::Window
+    };
+    (@tcp_field checksum) => {
+        $crate::expr::TcpHeaderField::Checksum
+    };
+    (@tcp_field urgptr) => {
+        $crate::expr::TcpHeaderField::UrgPtr
+    };
 
     (@udp_field sport) => {
         $crate::expr::UdpHeaderField::Sport
@@ -343,6 +908,43 @@ macro_rules! nft_expr_payload {
         $crate::expr::UdpHeaderField::Len
     };
 
+    (@icmp_field type) => {
+        $crate::expr::IcmpHeaderField::Type
+    };
+    (@icmp_field code) => {
+        $crate::expr::IcmpHeaderField::Code
+    };
+    (@icmp_field checksum) => {
+        $crate::expr::IcmpHeaderField::Checksum
+    };
+    (@icmp_field id) => {
+        $crate::expr::IcmpHeaderField::Id
+    };
+    (@icmp_field sequence) => {
+        $crate::expr::IcmpHeaderField::Sequence
+    };
+
+    (@sctp_field sport) => {
+        $crate::expr::SctpHeaderField::Sport
+    };
+    (@sctp_field dport) => {
+        $crate::expr::SctpHeaderField::Dport
+    };
+    (@sctp_field vtag) => {
+        $crate::expr::SctpHeaderField::VerificationTag
+    };
+    (@sctp_field checksum) => {
+        $crate::expr::SctpHeaderField::Checksum
+    };
+
+    ($proto:ident $($field:ident)+ => $reg:expr) => {
+        nft_expr_payload!($proto $($field)+).with_dest_reg($reg)
+    };
+
+    (write $proto:ident $field:ident from $reg:expr) => {
+        $crate::expr::PayloadWrite::new(nft_expr_payload!($proto $field), $reg)
+    };
+
     (ll $offset:expr, $length:expr) => {
         $crate::expr::Payload::LinkLayer($crate::expr::LLHeaderField::Raw { offset_bits: $offset, length_bits: $length })
     };
@@ -357,6 +959,12 @@ macro_rules! nft_expr_payload {
         $crate::expr::Payload::LinkLayer($crate::expr::LLHeaderField::EtherType)
     };
 
+    (vlan $field:ident) => {
+        $crate::expr::Payload::LinkLayer($crate::expr::LLHeaderField::Vlan(
+            nft_expr_payload!(@vlan_field $field),
+        ))
+    };
+
     (nh $offset:expr, $length:expr) => {
         $crate::expr::Payload::Network($crate::expr::NetworkHeaderField::Raw { offset_bits: $offset, length_bits: $length })
     };
@@ -372,6 +980,12 @@ macro_rules! nft_expr_payload {
         ))
     };
 
+    (arp $($field:ident)+) => {
+        $crate::expr::Payload::Network($crate::expr::NetworkHeaderField::Arp(
+            nft_expr_payload!(@arp_field $($field)+),
+        ))
+    };
+
     (th $offset:expr, $length:expr) => {
         $crate::expr::Payload::Transport($crate::expr::TransportHeaderField::Raw { offset_bits: $offset, length_bits: $length })
     };
@@ -386,4 +1000,14 @@ macro_rules! nft_expr_payload {
             nft_expr_payload!(@udp_field $field),
         ))
     };
+    (icmp $field:ident) => {
+        $crate::expr::Payload::Transport($crate::expr::TransportHeaderField::Icmp(
+            nft_expr_payload!(@icmp_field $field),
+        ))
+    };
+    (sctp $field:ident) => {
+        $crate::expr::Payload::Transport($crate::expr::TransportHeaderField::Sctp(
+            nft_expr_payload!(@sctp_field $field),
+        ))
+    };
 }
@@ -0,0 +1,87 @@
+use super::{Expression, Rule};
+use nftnl_sys as sys;
+use std::os::raw::c_char;
+use std::ptr;
+
+bitflags::bitflags! {
+    /// Flags for a [`Queue`] expression.
+    ///
+    /// [`Queue`]: struct.Queue.html
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct QueueFlags: u16 {
+        /// Accept the packet instead of dropping it if no program listens on the queue.
+        const BYPASS = 0x01;
+        /// Spread the packets over the queue range by CPU instead of by flow hash.
+        const CPU_FANOUT = 0x02;
+    }
+}
+
+/// Passes the packet to a userspace program listening on a netfilter queue, for example with
+/// libnetfilter_queue. The program then decides what to do with the packet.
+///
+/// When `queue_num_max` is set, the packets are spread over all queues from `queue_num` to
+/// `queue_num_max`, inclusive. The range can span at most 65535 queues, and `queue_num_max` can
+/// not be smaller than `queue_num`. [`Rule::try_add_expr`] returns an error for other ranges, and
+/// [`Rule::add_expr`] panics.
+///
+/// [`Rule::try_add_expr`]: ../struct.Rule.html#method.try_add_expr
+/// [`Rule::add_expr`]: ../struct.Rule.html#method.add_expr
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Queue {
+    pub queue_num: u16,
+    pub queue_num_max: Option<u16>,
+    pub flags: QueueFlags,
+}
+
+impl Expression for Queue {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        // The kernel takes the number of queues as a u16, so 0 - 65535 can not be represented.
+        let total = match self.queue_num_max {
+            Some(queue_num_max) => match queue_num_max
+                .checked_sub(self.queue_num)
+                .and_then(|span| span.checked_add(1))
+            {
+                Some(total) => total,
+                None => return ptr::null_mut(),
+            },
+            None => 1,
+        };
+
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"queue\0" as *const _ as *const c_char
+            ));
+
+            sys::nftnl_expr_set_u16(expr, sys::NFTNL_EXPR_QUEUE_NUM as u16, self.queue_num);
+            sys::nftnl_expr_set_u16(expr, sys::NFTNL_EXPR_QUEUE_TOTAL as u16, total);
+            sys::nftnl_expr_set_u16(expr, sys::NFTNL_EXPR_QUEUE_FLAGS as u16, self.flags.bits());
+
+            expr
+        }
+    }
+}
+
+#[macro_export(local_inner_macros)]
+macro_rules! nft_expr_queue {
+    (@flag bypass) => {
+        $crate::expr::QueueFlags::BYPASS
+    };
+    (@flag fanout) => {
+        $crate::expr::QueueFlags::CPU_FANOUT
+    };
+
+    (num $min:tt - $max:tt $($flag:ident)*) => {
+        $crate::expr::Queue {
+            queue_num: $min,
+            queue_num_max: Some($max),
+            flags: $crate::expr::QueueFlags::empty() $(| nft_expr_queue!(@flag $flag))*,
+        }
+    };
+    (num $num:tt $($flag:ident)*) => {
+        $crate::expr::Queue {
+            queue_num: $num,
+            queue_num_max: None,
+            flags: $crate::expr::QueueFlags::empty() $(| nft_expr_queue!(@flag $flag))*,
+        }
+    };
+}
@@ -0,0 +1,76 @@
+use super::{Expression, Rule, ToSlice};
+use nftnl_sys::{self as sys, libc};
+use std::ffi::c_void;
+use std::os::raw::c_char;
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_RANGE_EQ: u32 = 0;
+
+/// Range expression. Matches if the value in the register is within `from` and `to`, inclusive.
+/// Replaces a pair of `>=` and `<=` [`Cmp`] expressions.
+///
+/// Like `<` and `>` in [`Cmp`], the register content is compared byte by byte, as big endian.
+/// Multi byte integers must thus be converted with `to_be()`, which is also the byte order ports
+/// are loaded in by [`Payload`]: `nft_expr!(range (1024u16.to_be()) .. (2048u16.to_be()))`.
+///
+/// Requires the `nftnl-1-0-7` feature.
+///
+/// [`Cmp`]: struct.Cmp.html
+/// [`Payload`]: enum.Payload.html
+pub struct Range<T: ToSlice> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: ToSlice> Range<T> {
+    /// Returns a new range expression matching values between `from` and `to`, inclusive.
+    pub fn new(from: T, to: T) -> Self {
+        Range { from, to }
+    }
+}
+
+impl<T: ToSlice> Expression for Range<T> {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        unsafe {
+            let expr = try_alloc!(sys::nftnl_expr_alloc(
+                b"range\0" as *const _ as *const c_char
+            ));
+
+            let from = self.from.to_slice();
+            let to = self.to.to_slice();
+            trace!(
+                "Creating a range expr matching data from {:?} to {:?}",
+                from,
+                to
+            );
+
+            sys::nftnl_expr_set_u32(
+                expr,
+                sys::NFTNL_EXPR_RANGE_SREG as u16,
+                libc::NFT_REG_1 as u32,
+            );
+            sys::nftnl_expr_set_u32(expr, sys::NFTNL_EXPR_RANGE_OP as u16, NFT_RANGE_EQ);
+            sys::nftnl_expr_set(
+                expr,
+                sys::NFTNL_EXPR_RANGE_FROM_DATA as u16,
+                from.as_ref() as *const _ as *const c_void,
+                from.len() as u32,
+            );
+            sys::nftnl_expr_set(
+                expr,
+                sys::NFTNL_EXPR_RANGE_TO_DATA as u16,
+                to.as_ref() as *const _ as *const c_void,
+                to.len() as u32,
+            );
+
+            expr
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! nft_expr_range {
+    ($from:tt .. $to:tt) => {
+        $crate::expr::Range::new($from, $to)
+    };
+}
@@ -0,0 +1,109 @@
+//! Thin wrappers for reading attributes back out of a low level `nftnl_expr`.
+//!
+//! Every getter returns `None` if the attribute has not been set on the expression. This is
+//! checked with `nftnl_expr_is_set`, since `libnftnl` itself returns zero or a null pointer for
+//! unset attributes, which can't be told apart from a real value.
+//!
+//! # Safety
+//!
+//! All functions in this module require `expr` to be a valid pointer to an `nftnl_expr`, for
+//! example one returned by [`Expression::to_expr`]. Returned references borrow data owned by the
+//! expression and must not outlive it.
+//!
+//! [`Expression::to_expr`]: ../trait.Expression.html#tymethod.to_expr
+
+use nftnl_sys as sys;
+use std::{ffi::CStr, slice};
+
+/// Returns true if the attribute `attr` is set on `expr`.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn is_set(expr: *const sys::nftnl_expr, attr: u16) -> bool {
+    sys::nftnl_expr_is_set(expr, attr)
+}
+
+/// Returns the `u8` attribute `attr` of `expr`.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn get_u8(expr: *const sys::nftnl_expr, attr: u16) -> Option<u8> {
+    if is_set(expr, attr) {
+        Some(sys::nftnl_expr_get_u8(expr, attr))
+    } else {
+        None
+    }
+}
+
+/// Returns the `u16` attribute `attr` of `expr`.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn get_u16(expr: *const sys::nftnl_expr, attr: u16) -> Option<u16> {
+    if is_set(expr, attr) {
+        Some(sys::nftnl_expr_get_u16(expr, attr))
+    } else {
+        None
+    }
+}
+
+/// Returns the `u32` attribute `attr` of `expr`.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn get_u32(expr: *const sys::nftnl_expr, attr: u16) -> Option<u32> {
+    if is_set(expr, attr) {
+        Some(sys::nftnl_expr_get_u32(expr, attr))
+    } else {
+        None
+    }
+}
+
+/// Returns the `u64` attribute `attr` of `expr`.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn get_u64(expr: *const sys::nftnl_expr, attr: u16) -> Option<u64> {
+    if is_set(expr, attr) {
+        Some(sys::nftnl_expr_get_u64(expr, attr))
+    } else {
+        None
+    }
+}
+
+/// Returns the string attribute `attr` of `expr`.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn get_str<'a>(expr: *const sys::nftnl_expr, attr: u16) -> Option<&'a CStr> {
+    if is_set(expr, attr) {
+        let ptr = sys::nftnl_expr_get_str(expr, attr);
+        if !ptr.is_null() {
+            return Some(CStr::from_ptr(ptr));
+        }
+    }
+    None
+}
+
+/// Returns the raw data of attribute `attr` of `expr`, such as the data compared against by a
+/// `cmp` expression.
+///
+/// # Safety
+///
+/// See the [module level documentation](index.html#safety).
+pub unsafe fn get_data<'a>(expr: *const sys::nftnl_expr, attr: u16) -> Option<&'a [u8]> {
+    if is_set(expr, attr) {
+        let mut len = 0u32;
+        let ptr = sys::nftnl_expr_get(expr, attr, &mut len);
+        if !ptr.is_null() {
+            return Some(slice::from_raw_parts(ptr as *const u8, len as usize));
+        }
+    }
+    None
+}
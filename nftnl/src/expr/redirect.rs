@@ -0,0 +1,52 @@
+use super::{Expression, NatFlags, Register, Rule};
+use nftnl_sys as sys;
+use std::os::raw::c_char;
+
+/// Redirects the packet to the local machine, by changing its destination address to the
+/// primary address of the input interface. A special case of destination NAT, used in
+/// `prerouting` and `output` chains of `nat` type, for example for transparent proxies.
+///
+/// `Redirect::default()` keeps the destination port. Equivalent to `redirect` in `nft`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Redirect {
+    /// Register holding the port to redirect to, in network byte order. When `None` the
+    /// destination port is kept.
+    pub port_register: Option<Register>,
+    /// Register holding the last port of the port range to redirect to. Ignored without a
+    /// `port_register`. Equivalent to `redirect to :8000-8010`.
+    pub port_register_max: Option<Register>,
+    pub flags: NatFlags,
+}
+
+impl Expression for Redirect {
+    fn to_expr(&self, _rule: &Rule) -> *mut sys::nftnl_expr {
+        let expr =
+            try_alloc!(unsafe { sys::nftnl_expr_alloc(b"redir\0" as *const _ as *const c_char) });
+
+        unsafe {
+            if let Some(port_register) = self.port_register {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_REDIR_REG_PROTO_MIN as u16,
+                    port_register.to_raw(),
+                );
+                if let Some(port_register_max) = self.port_register_max {
+                    sys::nftnl_expr_set_u32(
+                        expr,
+                        sys::NFTNL_EXPR_REDIR_REG_PROTO_MAX as u16,
+                        port_register_max.to_raw(),
+                    );
+                }
+            }
+            if !self.flags.is_empty() {
+                sys::nftnl_expr_set_u32(
+                    expr,
+                    sys::NFTNL_EXPR_REDIR_FLAGS as u16,
+                    self.flags.bits(),
+                );
+            }
+        }
+
+        expr
+    }
+}
@@ -31,8 +31,16 @@ pub enum Verdict {
 /// The type of rejection message sent by the Reject verdict.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum RejectionType {
-    /// Return an ICMP unreachable packet
+    /// Return an ICMP unreachable packet. In `inet` and `bridge` tables this is an ICMPx reject,
+    /// answering with ICMP or ICMPv6 depending on the packet. In `ip` and `ip6` tables the code
+    /// is translated to the matching ICMP or ICMPv6 code, the same way the `nft` tool does it.
     Icmp(IcmpCode),
+    /// Return an ICMP packet with the given code. Only valid for IPv4 packets, so in `inet` and
+    /// `bridge` tables the rule must only match IPv4.
+    Icmpv4(Icmpv4Code),
+    /// Return an ICMPv6 packet with the given code. Only valid for IPv6 packets, so in `inet`
+    /// and `bridge` tables the rule must only match IPv6.
+    Icmpv6(Icmpv6Code),
     /// Reject by sending a TCP RST packet
     TcpRst,
 }
@@ -45,6 +53,7 @@ impl RejectionType {
                 ProtoFamily::Bridge | ProtoFamily::Inet => NFT_REJECT_ICMPX_UNREACH,
                 _ => NFT_REJECT_ICMP_UNREACH,
             },
+            RejectionType::Icmpv4(..) | RejectionType::Icmpv6(..) => NFT_REJECT_ICMP_UNREACH,
             RejectionType::TcpRst => NFT_REJECT_TCP_RST,
         };
         value as u32
@@ -61,6 +70,56 @@ pub enum IcmpCode {
     AdminProhibited = libc::NFT_REJECT_ICMPX_ADMIN_PROHIBITED as u8,
 }
 
+impl IcmpCode {
+    /// Returns the code to use in a reject expression in a table of the given family.
+    fn to_raw(self, family: ProtoFamily) -> u8 {
+        use self::IcmpCode::*;
+        match family {
+            ProtoFamily::Bridge | ProtoFamily::Inet => self as u8,
+            // ICMPV6_NOROUTE, ICMPV6_PORT_UNREACH, ICMPV6_ADDR_UNREACH, ICMPV6_ADM_PROHIBITED
+            ProtoFamily::Ipv6 => match self {
+                NoRoute => 0,
+                PortUnreach => 4,
+                HostUnreach => 3,
+                AdminProhibited => 1,
+            },
+            // ICMP_NET_UNREACH, ICMP_PORT_UNREACH, ICMP_HOST_UNREACH, ICMP_PKT_FILTERED
+            _ => match self {
+                NoRoute => 0,
+                PortUnreach => 3,
+                HostUnreach => 1,
+                AdminProhibited => 13,
+            },
+        }
+    }
+}
+
+/// An ICMP destination unreachable code, from linux/icmp.h.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum Icmpv4Code {
+    NetUnreach = 0,
+    HostUnreach = 1,
+    ProtoUnreach = 2,
+    PortUnreach = 3,
+    FragNeeded = 4,
+    NetProhibited = 9,
+    HostProhibited = 10,
+    AdminProhibited = 13,
+}
+
+/// An ICMPv6 destination unreachable code, from linux/icmpv6.h.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum Icmpv6Code {
+    NoRoute = 0,
+    AdminProhibited = 1,
+    AddrUnreach = 3,
+    PortUnreach = 4,
+    PolicyFail = 5,
+    RejectRoute = 6,
+}
+
 impl Verdict {
     unsafe fn to_immediate_expr(&self, immediate_const: i32) -> *mut sys::nftnl_expr {
         let expr = try_alloc!(sys::nftnl_expr_alloc(
@@ -101,7 +160,9 @@ impl Verdict {
         );
 
         let reject_code = match reject_type {
-            RejectionType::Icmp(code) => code as u8,
+            RejectionType::Icmp(code) => code.to_raw(family),
+            RejectionType::Icmpv4(code) => code as u8,
+            RejectionType::Icmpv6(code) => code as u8,
             RejectionType::TcpRst => 0,
         };
 
@@ -110,7 +171,23 @@ impl Verdict {
         expr
     }
 
-    fn chain(&self) -> Option<&CStr> {
+    /// Returns the `NF_*`/`NFT_*` verdict code of this verdict, or `None` for `Reject`, which is
+    /// a separate expression rather than a verdict code.
+    pub(crate) fn to_raw_verdict(&self) -> Option<i32> {
+        match *self {
+            Verdict::Drop => Some(libc::NF_DROP),
+            Verdict::Accept => Some(libc::NF_ACCEPT),
+            Verdict::Queue => Some(libc::NF_QUEUE),
+            Verdict::Continue => Some(libc::NFT_CONTINUE),
+            Verdict::Break => Some(libc::NFT_BREAK),
+            Verdict::Jump { .. } => Some(libc::NFT_JUMP),
+            Verdict::Goto { .. } => Some(libc::NFT_GOTO),
+            Verdict::Return => Some(libc::NFT_RETURN),
+            Verdict::Reject(..) => None,
+        }
+    }
+
+    pub(crate) fn chain(&self) -> Option<&CStr> {
         match *self {
             Verdict::Jump { ref chain } => Some(chain.as_c_str()),
             Verdict::Goto { ref chain } => Some(chain.as_c_str()),
@@ -121,21 +198,10 @@ impl Verdict {
 
 impl Expression for Verdict {
     fn to_expr(&self, rule: &Rule) -> *mut sys::nftnl_expr {
-        let immediate_const = match *self {
-            Verdict::Drop => libc::NF_DROP,
-            Verdict::Accept => libc::NF_ACCEPT,
-            Verdict::Queue => libc::NF_QUEUE,
-            Verdict::Continue => libc::NFT_CONTINUE,
-            Verdict::Break => libc::NFT_BREAK,
-            Verdict::Jump { .. } => libc::NFT_JUMP,
-            Verdict::Goto { .. } => libc::NFT_GOTO,
-            Verdict::Return => libc::NFT_RETURN,
-            Verdict::Reject(reject_type) => {
-                return unsafe {
-                    self.to_reject_expr(reject_type, rule.get_chain().get_table().get_family())
-                }
-            }
-        };
+        if let Verdict::Reject(reject_type) = *self {
+            return unsafe { self.to_reject_expr(reject_type, rule.get_table().get_family()) };
+        }
+        let immediate_const = self.to_raw_verdict().unwrap();
         unsafe { self.to_immediate_expr(immediate_const) }
     }
 }
@@ -148,11 +214,16 @@ macro_rules! nft_expr_verdict {
     (accept) => {
         $crate::expr::Verdict::Accept
     };
+    (reject) => {
+        $crate::expr::Verdict::Reject($crate::expr::RejectionType::Icmp(
+            $crate::expr::IcmpCode::PortUnreach,
+        ))
+    };
     (reject icmp $code:expr) => {
-        $crate::expr::Verdict::Reject(RejectionType::Icmp($code))
+        $crate::expr::Verdict::Reject($crate::expr::RejectionType::Icmp($code))
     };
     (reject tcp-rst) => {
-        $crate::expr::Verdict::Reject(RejectionType::TcpRst)
+        $crate::expr::Verdict::Reject($crate::expr::RejectionType::TcpRst)
     };
     (queue) => {
         $crate::expr::Verdict::Queue
@@ -25,6 +25,26 @@
 //! a number of places where the underlying library is used in an invalid or not intended way.
 //! Large portions of [`libnftnl`] are also not covered yet. Contributions are welcome!
 //!
+//! # Thread safety
+//!
+//! [`Table`], [`Chain`], [`Rule`] and [`Batch`] wrap raw pointers to `libnftnl` objects and
+//! implement `Send` and `Sync`. `libnftnl` itself does no locking, so two threads must never
+//! modify the same object at the same time. This crate upholds that through the borrow checker:
+//! every method that modifies the underlying object, such as [`Chain::set_hook`] or
+//! [`Rule::add_expr`], takes `&mut self`, while the methods taking `&self` only read from it.
+//! Sharing a `&Chain` between threads and reading from it, or serializing it into a batch, is thus
+//! safe, while building it needs exclusive access.
+//!
+//! To build an object from several threads, wrap it in a `Mutex` (for example
+//! `Arc<Mutex<Batch>>`) so only one thread modifies it at a time.
+//!
+//! Keep in mind that raw pointers handed out by methods such as [`Batch::as_raw_batch`] are not
+//! covered by this, and any use of them must be synchronized manually.
+//!
+//! [`Chain::set_hook`]: struct.Chain.html#method.set_hook
+//! [`Rule::add_expr`]: struct.Rule.html#method.add_expr
+//! [`Batch::as_raw_batch`]: struct.Batch.html#method.as_raw_batch
+//!
 //! # Selecting version of `libnftnl`
 //!
 //! See the documentation for the corresponding sys crate for details: [`nftnl-sys`]
@@ -54,7 +74,9 @@ macro_rules! try_alloc {
 }
 
 mod batch;
-pub use batch::{batch_is_supported, default_batch_page_size, Batch, FinalizedBatch, NetlinkError};
+pub use batch::{
+    batch_is_supported, default_batch_page_size, Batch, BatchError, FinalizedBatch, NetlinkError,
+};
 
 pub mod expr;
 
@@ -62,13 +84,33 @@ pub mod table;
 pub use table::Table;
 
 mod chain;
-pub use chain::{Chain, ChainType, Hook, Policy, Priority};
+pub use chain::{get_chains_cb, get_chains_nlmsg, Chain, ChainType, Hook, Policy, Priority};
+
+pub mod priority;
+
+#[cfg(feature = "nftnl-1-0-7")]
+pub mod object;
+
+pub mod policy;
 
 mod rule;
-pub use rule::Rule;
+pub use rule::{get_rules_cb, get_rules_nlmsg, Rule};
 
 pub mod set;
 
+pub mod diff;
+
+pub mod monitor;
+
+mod netlink;
+
+pub mod query;
+
+pub mod types;
+
+mod builder;
+pub use builder::{ChainBuilder, RuleBuilder, TableBuilder};
+
 /// The type of the message as it's sent to netfilter. A message consists of an object, such as a
 /// [`Table`], [`Chain`] or [`Rule`] for example, and a [`MsgType`] to describe what to do with
 /// that object. If a [`Table`] object is sent with `MsgType::Add` then that table will be added
@@ -79,11 +121,30 @@ pub mod set;
 /// [`Rule`]: struct.Rule.html
 /// [`MsgType`]: enum.MsgType.html
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
 pub enum MsgType {
     /// Add the object to netfilter.
     Add,
     /// Remove the object from netfilter.
     Del,
+    /// Remove the content of the object from netfilter, but keep the object itself. Flushing a
+    /// [`Table`] or a [`Chain`] deletes all rules in it, but no chains. Flushing a set deletes all
+    /// its elements. [`Rule`]s and named objects have no content, and [`Batch::add`] panics before
+    /// writing anything when they are added with `Flush`.
+    ///
+    /// [`Table`]: struct.Table.html
+    /// [`Chain`]: struct.Chain.html
+    /// [`Rule`]: struct.Rule.html
+    /// [`Batch::add`]: struct.Batch.html#method.add
+    Flush,
+    /// Replace a rule in netfilter with this one. The rule to replace is identified by the
+    /// handle set with [`Rule::set_handle`], and the new rule keeps that handle. The kernel swaps
+    /// the rules atomically, so no packet sees the chain without either of them. Only rules can
+    /// be replaced, and [`Batch::add`] panics before writing anything for any other object.
+    ///
+    /// [`Rule::set_handle`]: struct.Rule.html#method.set_handle
+    /// [`Batch::add`]: struct.Batch.html#method.add
+    Replace,
 }
 
 /// Denotes a protocol. Used to specify which protocol a table or set belongs to.
@@ -119,6 +180,15 @@ pub unsafe trait NlMsg {
     /// The caller must pass a `buf` with enough space for the largest possible netlink message.
     /// This size can be obtained with [`nft_nlmsg_maxsize`].
     unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType);
+
+    /// Returns whether this message can be written with `msg_type`. [`Batch::add`] checks this
+    /// before writing anything to the batch. All message types are supported by default.
+    ///
+    /// [`Batch::add`]: struct.Batch.html#method.add
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        let _ = msg_type;
+        true
+    }
 }
 
 /// The largest nf_tables netlink message is the set element message, which
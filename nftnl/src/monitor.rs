@@ -0,0 +1,97 @@
+//! Listening for changes to the nftables ruleset.
+//!
+//! The kernel sends a netlink multicast message to the `NFNLGRP_NFTABLES` group every time an
+//! object is added to or removed from the ruleset, no matter if the change was made by this
+//! process, the `nft` tool or anything else. A [`Monitor`] subscribes to these messages.
+//!
+//! [`Monitor`]: struct.Monitor.html
+
+use crate::netlink::{Messages, Socket, NFNL_SUBSYS_NFTABLES};
+use nftnl_sys::libc;
+use std::{
+    collections::VecDeque,
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+// From linux/netfilter/nfnetlink.h. Not exposed by the libc crate.
+const NFNLGRP_NFTABLES: libc::c_int = 7;
+
+/// A change to the ruleset, as reported by a [`Monitor`].
+///
+/// [`Monitor`]: struct.Monitor.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MonitorEvent {
+    NewTable,
+    DelTable,
+    NewChain,
+    DelChain,
+    NewRule,
+    DelRule,
+    NewSet,
+    DelSet,
+    /// Any other nftables message, such as set element or generation changes. Contains the
+    /// `NFT_MSG_*` message type.
+    Other(u16),
+}
+
+impl MonitorEvent {
+    fn from_raw(msg_type: u16) -> Self {
+        match libc::c_int::from(msg_type) {
+            libc::NFT_MSG_NEWTABLE => MonitorEvent::NewTable,
+            libc::NFT_MSG_DELTABLE => MonitorEvent::DelTable,
+            libc::NFT_MSG_NEWCHAIN => MonitorEvent::NewChain,
+            libc::NFT_MSG_DELCHAIN => MonitorEvent::DelChain,
+            libc::NFT_MSG_NEWRULE => MonitorEvent::NewRule,
+            libc::NFT_MSG_DELRULE => MonitorEvent::DelRule,
+            libc::NFT_MSG_NEWSET => MonitorEvent::NewSet,
+            libc::NFT_MSG_DELSET => MonitorEvent::DelSet,
+            _ => MonitorEvent::Other(msg_type),
+        }
+    }
+}
+
+/// A netlink socket subscribed to nftables ruleset changes. Requires `CAP_NET_ADMIN`.
+pub struct Monitor {
+    socket: Socket,
+    pending: VecDeque<MonitorEvent>,
+}
+
+impl Monitor {
+    /// Opens a `NETLINK_NETFILTER` socket and joins the `NFNLGRP_NFTABLES` multicast group.
+    pub fn new() -> io::Result<Self> {
+        let socket = Socket::open()?;
+        socket.join_group(NFNLGRP_NFTABLES)?;
+        Ok(Monitor {
+            socket,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Blocks until the next ruleset change and returns it. `buf` is used to receive the netlink
+    /// messages. It should be at least [`nft_nlmsg_maxsize`] bytes, or messages may be truncated.
+    ///
+    /// A single netlink datagram can carry several events. Those are queued up and returned by
+    /// the following calls without reading from the socket.
+    ///
+    /// [`nft_nlmsg_maxsize`]: ../fn.nft_nlmsg_maxsize.html
+    pub fn recv(&mut self, buf: &mut [u8]) -> io::Result<MonitorEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            for header in Messages::new(self.socket.recv(buf)?) {
+                if header.nlmsg_type >> 8 == NFNL_SUBSYS_NFTABLES {
+                    self.pending
+                        .push_back(MonitorEvent::from_raw(header.nlmsg_type & 0xff));
+                }
+            }
+        }
+    }
+}
+
+impl AsRawFd for Monitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
@@ -0,0 +1,133 @@
+//! A minimal `NETLINK_NETFILTER` socket, used by the modules that talk to the kernel directly.
+
+use nftnl_sys::libc;
+use std::{
+    ffi::c_void,
+    io, mem,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+// From linux/netfilter/nfnetlink.h. Not exposed by the libc crate.
+pub(crate) const NFNL_SUBSYS_NFTABLES: u16 = 10;
+
+pub(crate) struct Socket {
+    fd: RawFd,
+}
+
+impl Socket {
+    /// Opens and binds a `NETLINK_NETFILTER` socket.
+    pub fn open() -> io::Result<Self> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_NETFILTER,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Closes the socket on the error path below.
+        let socket = Socket { fd };
+
+        let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        let ret = unsafe {
+            libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(socket)
+    }
+
+    /// Joins the netlink multicast group `group`.
+    pub fn join_group(&self, group: libc::c_int) -> io::Result<()> {
+        let ret = unsafe {
+            libc::setsockopt(
+                self.fd,
+                libc::SOL_NETLINK,
+                libc::NETLINK_ADD_MEMBERSHIP,
+                &group as *const libc::c_int as *const c_void,
+                mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub fn send(&self, data: &[u8]) -> io::Result<()> {
+        let ret = unsafe { libc::send(self.fd, data.as_ptr() as *const c_void, data.len(), 0) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Receives one datagram into `buf` and returns the received part of it.
+    pub fn recv<'a>(&self, buf: &'a mut [u8]) -> io::Result<&'a [u8]> {
+        let len = unsafe { libc::recv(self.fd, buf.as_mut_ptr() as *mut c_void, buf.len(), 0) };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(&buf[..len as usize])
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+/// Iterates over the netlink messages in a received datagram. Stops at the first truncated
+/// message.
+pub(crate) struct Messages<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Messages<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Messages { data }
+    }
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = &'a libc::nlmsghdr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header_len = mem::size_of::<libc::nlmsghdr>();
+        if self.data.len() < header_len {
+            return None;
+        }
+        // The kernel pads every netlink message to four bytes, so this only fails if the receive
+        // buffer itself is misaligned.
+        if self.data.as_ptr() as usize % mem::align_of::<libc::nlmsghdr>() != 0 {
+            warn!("Ignoring netlink messages in misaligned buffer");
+            self.data = &[];
+            return None;
+        }
+        let header = unsafe { &*(self.data.as_ptr() as *const libc::nlmsghdr) };
+        let msg_len = header.nlmsg_len as usize;
+        if msg_len < header_len || msg_len > self.data.len() {
+            warn!("Ignoring truncated netlink message");
+            self.data = &[];
+            return None;
+        }
+        let aligned_len = (msg_len + 3) & !3;
+        self.data = self.data.get(aligned_len..).unwrap_or(&[]);
+        Some(header)
+    }
+}
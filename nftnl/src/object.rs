@@ -0,0 +1,497 @@
+//! Named stateful objects, such as counters, that live in a table and can be shared by the
+//! rules in it. A rule uses an object through a reference expression, such as [`CounterRef`].
+//!
+//! Requires the `nftnl-1-0-7` feature.
+//!
+//! [`CounterRef`]: ../expr/struct.CounterRef.html
+
+#[cfg(feature = "nftnl-1-0-8")]
+use crate::expr::{LimitPer, LimitUnit, NFT_LIMIT_F_INV};
+use crate::{query::get_str, table::Table, MsgType, ProtoFamily};
+use nftnl_sys::{self as sys, libc};
+#[cfg(feature = "nftnl-1-1-2")]
+use std::{collections::HashMap, time::Duration};
+use std::{
+    ffi::{c_void, CStr, CString},
+    os::raw::c_char,
+};
+
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+pub(crate) const NFT_OBJECT_COUNTER: u32 = 1;
+pub(crate) const NFT_OBJECT_QUOTA: u32 = 2;
+#[cfg(feature = "nftnl-1-0-8")]
+pub(crate) const NFT_OBJECT_CT_HELPER: u32 = 3;
+#[cfg(feature = "nftnl-1-0-8")]
+pub(crate) const NFT_OBJECT_LIMIT: u32 = 4;
+#[cfg(feature = "nftnl-1-1-2")]
+pub(crate) const NFT_OBJECT_CT_TIMEOUT: u32 = 7;
+
+/// A named counter, `counter name { }` in `nft`. Counts the packets and bytes of all rules
+/// referencing it with a [`CounterRef`] expression.
+///
+/// [`CounterRef`]: ../expr/struct.CounterRef.html
+pub struct NamedCounter<'a> {
+    pub name: CString,
+    pub table: &'a Table,
+    /// The initial number of packets.
+    pub packets: u64,
+    /// The initial number of bytes.
+    pub bytes: u64,
+}
+
+impl<'a> NamedCounter<'a> {
+    /// Returns a counter starting at zero.
+    pub fn new(name: &CStr, table: &'a Table) -> Self {
+        NamedCounter {
+            name: name.to_owned(),
+            table,
+            packets: 0,
+            bytes: 0,
+        }
+    }
+}
+
+unsafe impl<'a> crate::NlMsg for NamedCounter<'a> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        let obj = alloc_obj(&self.name, self.table, NFT_OBJECT_COUNTER);
+        sys::nftnl_obj_set_u64(obj, sys::NFTNL_OBJ_CTR_PKTS as u16, self.packets);
+        sys::nftnl_obj_set_u64(obj, sys::NFTNL_OBJ_CTR_BYTES as u16, self.bytes);
+        write_obj(obj, self.table, buf, seq, msg_type);
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        matches!(msg_type, MsgType::Add | MsgType::Del)
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags of a [`NamedQuota`].
+    ///
+    /// [`NamedQuota`]: struct.NamedQuota.html
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct QuotaFlags: u32 {
+        /// Match once the quota is used up, instead of until it is. `quota over` in `nft`.
+        const INVERT = 1;
+    }
+}
+
+/// A named quota, `quota name { }` in `nft`. Sums up the bytes of the packets passing the
+/// rules referencing it with a [`QuotaRef`] expression. The expression matches until `bytes`
+/// bytes have passed, or after that with [`QuotaFlags::INVERT`].
+///
+/// [`QuotaRef`]: ../expr/struct.QuotaRef.html
+/// [`QuotaFlags::INVERT`]: struct.QuotaFlags.html#associatedconstant.INVERT
+pub struct NamedQuota<'a> {
+    pub name: CString,
+    pub table: &'a Table,
+    pub bytes: u64,
+    pub flags: QuotaFlags,
+}
+
+impl<'a> NamedQuota<'a> {
+    pub fn new(name: &CStr, table: &'a Table, bytes: u64) -> Self {
+        NamedQuota {
+            name: name.to_owned(),
+            table,
+            bytes,
+            flags: QuotaFlags::empty(),
+        }
+    }
+}
+
+unsafe impl<'a> crate::NlMsg for NamedQuota<'a> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        let obj = alloc_obj(&self.name, self.table, NFT_OBJECT_QUOTA);
+        sys::nftnl_obj_set_u64(obj, sys::NFTNL_OBJ_QUOTA_BYTES as u16, self.bytes);
+        sys::nftnl_obj_set_u32(obj, sys::NFTNL_OBJ_QUOTA_FLAGS as u16, self.flags.bits());
+        write_obj(obj, self.table, buf, seq, msg_type);
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        matches!(msg_type, MsgType::Add | MsgType::Del)
+    }
+}
+
+/// A named limit, `limit name { }` in `nft`. All rules referencing it with a [`LimitRef`]
+/// expression share the same token bucket, so their combined rate is limited. The fields work
+/// like the ones of the [`Limit`] expression.
+///
+/// Requires the `nftnl-1-0-8` feature.
+///
+/// [`LimitRef`]: ../expr/struct.LimitRef.html
+/// [`Limit`]: ../expr/struct.Limit.html
+#[cfg(feature = "nftnl-1-0-8")]
+pub struct NamedLimit<'a> {
+    pub name: CString,
+    pub table: &'a Table,
+    pub rate: u64,
+    pub unit: LimitUnit,
+    pub per: LimitPer,
+    pub burst: u32,
+    /// Match the packets above the rate instead of the ones within it.
+    pub inverse: bool,
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+impl<'a> NamedLimit<'a> {
+    /// Creates a limit with the same default burst as [`Limit::new`].
+    ///
+    /// [`Limit::new`]: ../expr/struct.Limit.html#method.new
+    pub fn new(name: &CStr, table: &'a Table, rate: u64, unit: LimitUnit, per: LimitPer) -> Self {
+        let limit = crate::expr::Limit::new(rate, unit, per);
+        NamedLimit {
+            name: name.to_owned(),
+            table,
+            rate,
+            unit,
+            per,
+            burst: limit.burst,
+            inverse: false,
+        }
+    }
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+unsafe impl<'a> crate::NlMsg for NamedLimit<'a> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        let obj = alloc_obj(&self.name, self.table, NFT_OBJECT_LIMIT);
+        sys::nftnl_obj_set_u64(obj, sys::NFTNL_OBJ_LIMIT_RATE as u16, self.rate);
+        sys::nftnl_obj_set_u64(obj, sys::NFTNL_OBJ_LIMIT_UNIT as u16, self.per.seconds());
+        sys::nftnl_obj_set_u32(obj, sys::NFTNL_OBJ_LIMIT_BURST as u16, self.burst);
+        sys::nftnl_obj_set_u32(obj, sys::NFTNL_OBJ_LIMIT_TYPE as u16, self.unit.to_raw());
+        if self.inverse {
+            sys::nftnl_obj_set_u32(obj, sys::NFTNL_OBJ_LIMIT_FLAGS as u16, NFT_LIMIT_F_INV);
+        }
+        write_obj(obj, self.table, buf, seq, msg_type);
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        matches!(msg_type, MsgType::Add | MsgType::Del)
+    }
+}
+
+/// A conntrack helper, `ct helper name { }` in `nft`. Rules assign it to connections with a
+/// [`CtHelperRef`] expression, after which the kernel helper `helper` tracks the related
+/// connections of that protocol, such as the data connections of FTP.
+///
+/// Requires the `nftnl-1-0-8` feature.
+///
+/// [`CtHelperRef`]: ../expr/struct.CtHelperRef.html
+#[cfg(feature = "nftnl-1-0-8")]
+pub struct CtHelper<'a> {
+    pub name: CString,
+    pub table: &'a Table,
+    /// Name of the kernel helper, such as `ftp` or `sip`.
+    pub helper: CString,
+    /// The layer 4 protocol of the connections, such as `libc::IPPROTO_TCP`.
+    pub l4proto: u8,
+    /// The layer 3 protocol of the connections.
+    pub l3num: ProtoFamily,
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+impl<'a> CtHelper<'a> {
+    /// Creates a helper for connections of the same family as `table`.
+    pub fn new(name: &CStr, table: &'a Table, helper: &CStr, l4proto: u8) -> Self {
+        CtHelper {
+            name: name.to_owned(),
+            table,
+            helper: helper.to_owned(),
+            l4proto,
+            l3num: table.get_family(),
+        }
+    }
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+unsafe impl<'a> crate::NlMsg for CtHelper<'a> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        let obj = alloc_obj(&self.name, self.table, NFT_OBJECT_CT_HELPER);
+        sys::nftnl_obj_set_str(
+            obj,
+            sys::NFTNL_OBJ_CT_HELPER_NAME as u16,
+            self.helper.as_ptr(),
+        );
+        sys::nftnl_obj_set_u16(
+            obj,
+            sys::NFTNL_OBJ_CT_HELPER_L3PROTO as u16,
+            self.l3num as u16,
+        );
+        sys::nftnl_obj_set_u8(obj, sys::NFTNL_OBJ_CT_HELPER_L4PROTO as u16, self.l4proto);
+        write_obj(obj, self.table, buf, seq, msg_type);
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        matches!(msg_type, MsgType::Add | MsgType::Del)
+    }
+}
+
+/// A protocol state with a timeout configurable by a [`CtTimeout`] policy.
+///
+/// Requires the `nftnl-1-1-2` feature.
+///
+/// [`CtTimeout`]: struct.CtTimeout.html
+#[cfg(feature = "nftnl-1-1-2")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CtTimeoutState {
+    TcpSynSent,
+    TcpSynRecv,
+    TcpEstablished,
+    TcpFinWait,
+    TcpCloseWait,
+    TcpLastAck,
+    TcpTimeWait,
+    TcpClose,
+    TcpSynSent2,
+    TcpRetrans,
+    TcpUnack,
+    UdpUnreplied,
+    UdpReplied,
+}
+
+#[cfg(feature = "nftnl-1-1-2")]
+impl CtTimeoutState {
+    const TCP: [CtTimeoutState; 11] = [
+        CtTimeoutState::TcpSynSent,
+        CtTimeoutState::TcpSynRecv,
+        CtTimeoutState::TcpEstablished,
+        CtTimeoutState::TcpFinWait,
+        CtTimeoutState::TcpCloseWait,
+        CtTimeoutState::TcpLastAck,
+        CtTimeoutState::TcpTimeWait,
+        CtTimeoutState::TcpClose,
+        CtTimeoutState::TcpSynSent2,
+        CtTimeoutState::TcpRetrans,
+        CtTimeoutState::TcpUnack,
+    ];
+    const UDP: [CtTimeoutState; 2] = [CtTimeoutState::UdpUnreplied, CtTimeoutState::UdpReplied];
+
+    /// Returns the states of the layer 4 protocol `l4proto`, or `None` if timeout policies
+    /// are not supported for it.
+    fn states_of(l4proto: u8) -> Option<&'static [CtTimeoutState]> {
+        match libc::c_int::from(l4proto) {
+            libc::IPPROTO_TCP => Some(&Self::TCP),
+            libc::IPPROTO_UDP => Some(&Self::UDP),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of the state in the timeout array of its protocol.
+    fn index(self) -> usize {
+        use self::CtTimeoutState::*;
+        (match self {
+            TcpSynSent => sys::NFTNL_CTTIMEOUT_TCP_SYN_SENT,
+            TcpSynRecv => sys::NFTNL_CTTIMEOUT_TCP_SYN_RECV,
+            TcpEstablished => sys::NFTNL_CTTIMEOUT_TCP_ESTABLISHED,
+            TcpFinWait => sys::NFTNL_CTTIMEOUT_TCP_FIN_WAIT,
+            TcpCloseWait => sys::NFTNL_CTTIMEOUT_TCP_CLOSE_WAIT,
+            TcpLastAck => sys::NFTNL_CTTIMEOUT_TCP_LAST_ACK,
+            TcpTimeWait => sys::NFTNL_CTTIMEOUT_TCP_TIME_WAIT,
+            TcpClose => sys::NFTNL_CTTIMEOUT_TCP_CLOSE,
+            TcpSynSent2 => sys::NFTNL_CTTIMEOUT_TCP_SYN_SENT2,
+            TcpRetrans => sys::NFTNL_CTTIMEOUT_TCP_RETRANS,
+            TcpUnack => sys::NFTNL_CTTIMEOUT_TCP_UNACK,
+            UdpUnreplied => sys::NFTNL_CTTIMEOUT_UDP_UNREPLIED,
+            UdpReplied => sys::NFTNL_CTTIMEOUT_UDP_REPLIED,
+        }) as usize
+    }
+
+    /// Returns the default timeout of the state in seconds, the same as the kernel and `nft`
+    /// use.
+    fn default_secs(self) -> u32 {
+        use self::CtTimeoutState::*;
+        match self {
+            TcpSynSent => 120,
+            TcpSynRecv => 60,
+            TcpEstablished => 432_000,
+            TcpFinWait => 120,
+            TcpCloseWait => 60,
+            TcpLastAck => 30,
+            TcpTimeWait => 120,
+            TcpClose => 10,
+            TcpSynSent2 => 120,
+            TcpRetrans => 300,
+            TcpUnack => 300,
+            UdpUnreplied => 30,
+            UdpReplied => 180,
+        }
+    }
+}
+
+/// A conntrack timeout policy, `ct timeout name { }` in `nft`. Rules assign it to connections
+/// with a [`CtTimeoutRef`] expression. States missing from `timeout_map` keep their default
+/// timeout. Timeouts are sent to the kernel in whole seconds.
+///
+/// Only TCP and UDP policies are supported.
+///
+/// Requires the `nftnl-1-1-2` feature.
+///
+/// [`CtTimeoutRef`]: ../expr/struct.CtTimeoutRef.html
+#[cfg(feature = "nftnl-1-1-2")]
+pub struct CtTimeout<'a> {
+    pub name: CString,
+    pub table: &'a Table,
+    /// The layer 4 protocol of the connections, `libc::IPPROTO_TCP` or `libc::IPPROTO_UDP`.
+    pub l4proto: u8,
+    /// The layer 3 protocol of the connections.
+    pub l3num: ProtoFamily,
+    pub timeout_map: HashMap<CtTimeoutState, Duration>,
+}
+
+#[cfg(feature = "nftnl-1-1-2")]
+impl<'a> CtTimeout<'a> {
+    /// Creates a policy with the default timeouts for connections of the same family as
+    /// `table`.
+    pub fn new(name: &CStr, table: &'a Table, l4proto: u8) -> Self {
+        CtTimeout {
+            name: name.to_owned(),
+            table,
+            l4proto,
+            l3num: table.get_family(),
+            timeout_map: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(feature = "nftnl-1-1-2")]
+unsafe impl<'a> crate::NlMsg for CtTimeout<'a> {
+    /// # Panics
+    ///
+    /// Panics if `l4proto` is neither TCP nor UDP, or if `timeout_map` contains states of
+    /// another protocol.
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        let states = CtTimeoutState::states_of(self.l4proto)
+            .expect("Timeout policies are only supported for TCP and UDP");
+        let mut timeouts = [0u32; sys::NFTNL_CTTIMEOUT_TCP_MAX as usize];
+        for state in states {
+            timeouts[state.index()] = state.default_secs();
+        }
+        for (state, timeout) in &self.timeout_map {
+            assert!(
+                states.contains(state),
+                "{:?} is not a state of protocol {}",
+                state,
+                self.l4proto
+            );
+            timeouts[state.index()] = timeout.as_secs() as u32;
+        }
+
+        let obj = alloc_obj(&self.name, self.table, NFT_OBJECT_CT_TIMEOUT);
+        sys::nftnl_obj_set_u16(
+            obj,
+            sys::NFTNL_OBJ_CT_TIMEOUT_L3PROTO as u16,
+            self.l3num as u16,
+        );
+        sys::nftnl_obj_set_u8(obj, sys::NFTNL_OBJ_CT_TIMEOUT_L4PROTO as u16, self.l4proto);
+        sys::nftnl_obj_set(
+            obj,
+            sys::NFTNL_OBJ_CT_TIMEOUT_ARRAY as u16,
+            timeouts.as_ptr() as *const c_void,
+        );
+        write_obj(obj, self.table, buf, seq, msg_type);
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        matches!(msg_type, MsgType::Add | MsgType::Del)
+    }
+}
+
+/// An object loaded in the kernel, as collected by [`get_objects_cb`].
+///
+/// [`get_objects_cb`]: fn.get_objects_cb.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ObjectInfo {
+    pub name: CString,
+    /// Name of the table the object belongs to.
+    pub table: CString,
+    /// The `NFT_OBJECT_*` type of the object, such as 1 for counters and 2 for quotas.
+    pub obj_type: u32,
+}
+
+/// Returns a buffer containing a netlink message which requests a list of all the objects in
+/// all tables of `family`.
+pub fn get_objects_nlmsg(seq: u32, family: ProtoFamily) -> Vec<u8> {
+    let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+    let _ = unsafe {
+        sys::nftnl_nlmsg_build_hdr(
+            buffer.as_mut_ptr() as *mut c_char,
+            libc::NFT_MSG_GETOBJ as u16,
+            family as u16,
+            (libc::NLM_F_ROOT | libc::NLM_F_MATCH) as u16,
+            seq,
+        )
+    };
+    buffer
+}
+
+/// A callback to parse the response for messages created with `get_objects_nlmsg`. This
+/// callback appends the objects in the response to `objects`. Objects missing their name or table
+/// are skipped.
+pub fn get_objects_cb(header: &libc::nlmsghdr, objects: &mut Vec<ObjectInfo>) -> libc::c_int {
+    unsafe {
+        let obj = try_alloc!(sys::nftnl_obj_alloc());
+        let err = sys::nftnl_obj_nlmsg_parse(header, obj);
+        if err < 0 {
+            error!("Failed to parse netlink object message - {}", err);
+            sys::nftnl_obj_free(obj);
+            return err;
+        }
+        let name = get_str(sys::nftnl_obj_get_str(obj, sys::NFTNL_OBJ_NAME as u16));
+        let table = get_str(sys::nftnl_obj_get_str(obj, sys::NFTNL_OBJ_TABLE as u16));
+        if let (Some(name), Some(table)) = (name, table) {
+            objects.push(ObjectInfo {
+                name,
+                table,
+                obj_type: sys::nftnl_obj_get_u32(obj, sys::NFTNL_OBJ_TYPE as u16),
+            });
+        }
+        sys::nftnl_obj_free(obj);
+    }
+    1
+}
+
+/// Allocates an object of type `obj_type` named `name` in `table`.
+unsafe fn alloc_obj(name: &CStr, table: &Table, obj_type: u32) -> *mut sys::nftnl_obj {
+    let obj = try_alloc!(sys::nftnl_obj_alloc());
+    sys::nftnl_obj_set_u32(obj, sys::NFTNL_OBJ_FAMILY as u16, table.get_family() as u32);
+    sys::nftnl_obj_set_str(obj, sys::NFTNL_OBJ_TABLE as u16, table.get_name().as_ptr());
+    sys::nftnl_obj_set_str(obj, sys::NFTNL_OBJ_NAME as u16, name.as_ptr());
+    sys::nftnl_obj_set_u32(obj, sys::NFTNL_OBJ_TYPE as u16, obj_type);
+    obj
+}
+
+/// Writes a message adding or deleting `obj` to `buf` and frees `obj`.
+///
+/// # Panics
+///
+/// Panics on `MsgType::Flush`, since objects have no content that could be flushed, and on
+/// `MsgType::Replace`.
+unsafe fn write_obj(
+    obj: *mut sys::nftnl_obj,
+    table: &Table,
+    buf: *mut c_void,
+    seq: u32,
+    msg_type: MsgType,
+) {
+    let (raw_msg_type, flags) = match msg_type {
+        MsgType::Add => (libc::NFT_MSG_NEWOBJ, libc::NLM_F_ACK | libc::NLM_F_CREATE),
+        MsgType::Del => (libc::NFT_MSG_DELOBJ, libc::NLM_F_ACK),
+        MsgType::Flush => {
+            sys::nftnl_obj_free(obj);
+            panic!("Objects can not be flushed");
+        }
+        MsgType::Replace => {
+            sys::nftnl_obj_free(obj);
+            panic!("Objects can not be replaced");
+        }
+    };
+    let header = sys::nftnl_nlmsg_build_hdr(
+        buf as *mut c_char,
+        raw_msg_type as u16,
+        table.get_family() as u16,
+        flags as u16,
+        seq,
+    );
+    sys::nftnl_obj_nlmsg_build_payload(header, obj);
+    sys::nftnl_obj_free(obj);
+}
@@ -0,0 +1,59 @@
+//! Ready made chains and rules for common firewall setups, similar to what ufw or firewalld set
+//! up by default.
+//!
+//! Everything here is built only from the public API of this crate, so the source also serves as
+//! an example of how to write these rules by hand.
+
+use crate::{
+    expr::{InterfaceName, States},
+    nft_expr, priority, Batch, Chain, Hook, MsgType, Policy, Rule, Table,
+};
+use std::ffi::CString;
+
+/// Adds a chain named `input` to `table` that drops all incoming traffic, except on the loopback
+/// interface and traffic belonging to connections that are already established. This corresponds
+/// to:
+///
+/// ```text
+/// chain input {
+///     type filter hook input priority filter; policy drop;
+///     iifname "lo" accept
+///     ct state established,related accept
+/// }
+/// ```
+///
+/// `table` must already exist in the kernel or have been added to `batch`. More rules can be
+/// appended to the chain by name later, for example to open up ports.
+pub fn default_drop_input(table: &Table, batch: &mut Batch) {
+    let mut chain = Chain::new(&CString::new("input").unwrap(), table);
+    chain.set_hook(Hook::In, priority::FILTER);
+    chain.set_policy(Policy::Drop);
+    batch.add(&chain, MsgType::Add);
+
+    batch.add(&allow_loopback(&chain), MsgType::Add);
+    batch.add(&allow_established_related(&chain), MsgType::Add);
+}
+
+/// Returns a rule accepting packets that belong to, or are related to, an already established
+/// connection. Corresponds to `ct state established,related accept`.
+pub fn allow_established_related<'a>(chain: &'a Chain<'_>) -> Rule<'a> {
+    let mut rule = Rule::new(chain);
+    let allowed_states = (States::ESTABLISHED | States::RELATED).bits();
+    rule.add_expr(&nft_expr!(ct state));
+    rule.add_expr(&nft_expr!(bitwise mask allowed_states, xor 0u32));
+    rule.add_expr(&nft_expr!(cmp != 0u32));
+    rule.add_expr(&nft_expr!(verdict accept));
+    rule
+}
+
+/// Returns a rule accepting all packets coming in on the loopback interface. Corresponds to
+/// `iifname "lo" accept`.
+pub fn allow_loopback<'a>(chain: &'a Chain<'_>) -> Rule<'a> {
+    let mut rule = Rule::new(chain);
+    rule.add_expr(&nft_expr!(meta iifname));
+    rule.add_expr(&nft_expr!(
+        cmp == InterfaceName::Exact(CString::new("lo").unwrap())
+    ));
+    rule.add_expr(&nft_expr!(verdict accept));
+    rule
+}
@@ -0,0 +1,48 @@
+//! Standard chain priorities, as named by the `nft` tool. Lower values are evaluated first.
+//!
+//! The constants in this module apply to the `ip`, `ip6` and `inet` families. The `bridge` and
+//! `netdev` families have their own, see the submodules. `nft` lets you write for example
+//! `priority filter + 10`, which translates to `FILTER + 10` here.
+
+use crate::Priority;
+use nftnl_sys::libc;
+
+/// Priority of the defragmentation done before connection tracking. Chains hooked before it
+/// see the fragments of packets rather than whole packets.
+pub const CONNTRACK_DEFRAG: Priority = libc::NF_IP_PRI_CONNTRACK_DEFRAG;
+/// `raw`. Evaluated before connection tracking.
+pub const RAW: Priority = libc::NF_IP_PRI_RAW;
+/// Priority of connection tracking itself.
+pub const CONNTRACK: Priority = libc::NF_IP_PRI_CONNTRACK;
+/// `mangle`. Packet modification.
+pub const MANGLE: Priority = libc::NF_IP_PRI_MANGLE;
+/// `dstnat`. Destination NAT.
+pub const DSTNAT: Priority = libc::NF_IP_PRI_NAT_DST;
+/// `filter`. Regular packet filtering.
+pub const FILTER: Priority = libc::NF_IP_PRI_FILTER;
+/// `security`. Where SELinux and other security modules apply their rules.
+pub const SECURITY: Priority = libc::NF_IP_PRI_SECURITY;
+/// `srcnat`. Source NAT.
+pub const SRCNAT: Priority = libc::NF_IP_PRI_NAT_SRC;
+
+/// Standard priorities in the `bridge` family.
+pub mod bridge {
+    use crate::Priority;
+
+    /// `dstnat`. Destination NAT.
+    pub const DSTNAT: Priority = -300;
+    /// `filter`. Regular packet filtering.
+    pub const FILTER: Priority = -200;
+    /// `out`. Packets leaving the bridge.
+    pub const OUT: Priority = 100;
+    /// `srcnat`. Source NAT.
+    pub const SRCNAT: Priority = 300;
+}
+
+/// Standard priorities in the `netdev` family.
+pub mod netdev {
+    use crate::Priority;
+
+    /// `filter`. Regular packet filtering.
+    pub const FILTER: Priority = 0;
+}
@@ -0,0 +1,283 @@
+//! Listing the tables, chains and rules currently loaded in the kernel.
+//!
+//! Each function opens its own netlink socket, sends a dump request and collects the replies.
+//! Reading the ruleset requires `CAP_NET_ADMIN`.
+
+use crate::{
+    netlink::{Messages, Socket},
+    ChainType, Hook, Policy, ProtoFamily,
+};
+use nftnl_sys::{self as sys, libc};
+use std::{
+    ffi::{CStr, CString},
+    io,
+    os::raw::c_char,
+};
+
+/// A table loaded in the kernel, as returned by [`list_tables`].
+///
+/// [`list_tables`]: fn.list_tables.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TableInfo {
+    pub name: CString,
+    pub family: ProtoFamily,
+}
+
+/// A chain loaded in the kernel, as returned by [`list_chains`].
+///
+/// [`list_chains`]: fn.list_chains.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ChainInfo {
+    pub name: CString,
+    /// Name of the table the chain belongs to.
+    pub table: CString,
+    pub family: ProtoFamily,
+    /// The hook of a base chain. `None` for regular chains.
+    pub hook: Option<Hook>,
+    /// The policy of a base chain. `None` for regular chains.
+    pub policy: Option<Policy>,
+    /// The type of a base chain. `None` for regular chains.
+    pub chain_type: Option<ChainType>,
+}
+
+/// A rule loaded in the kernel, as returned by [`list_rules`].
+///
+/// [`list_rules`]: fn.list_rules.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RuleInfo {
+    /// Name of the table the rule belongs to.
+    pub table: CString,
+    /// Name of the chain the rule belongs to.
+    pub chain: CString,
+    pub family: ProtoFamily,
+    /// The handle the kernel assigned to the rule. Identifies the rule within its chain.
+    pub handle: u64,
+    /// The handle of the rule before this one in the chain, or 0 for the first rule. Always 0 for
+    /// rules echoed back by [`Rule::set_echo`], since the kernel only reports positions in dumps.
+    ///
+    /// [`Rule::set_echo`]: ../struct.Rule.html#method.set_echo
+    pub position: u64,
+}
+
+/// Lists all tables in `family`. Pass `ProtoFamily::Unspec` to list the tables of all families.
+pub fn list_tables(family: ProtoFamily) -> io::Result<Vec<TableInfo>> {
+    dump(
+        libc::NFT_MSG_GETTABLE,
+        family,
+        |_| (),
+        |header| unsafe {
+            let table = try_alloc!(sys::nftnl_table_alloc());
+            let result = if sys::nftnl_table_nlmsg_parse(header, table) < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(parse_family(sys::nftnl_table_get_u32(
+                    table,
+                    sys::NFTNL_TABLE_FAMILY as u16,
+                ))
+                .and_then(|family| {
+                    Some(TableInfo {
+                        name: get_str(sys::nftnl_table_get_str(
+                            table,
+                            sys::NFTNL_TABLE_NAME as u16,
+                        ))?,
+                        family,
+                    })
+                }))
+            };
+            sys::nftnl_table_free(table);
+            result
+        },
+    )
+}
+
+/// Lists all chains in `table`.
+pub fn list_chains(table: &TableInfo) -> io::Result<Vec<ChainInfo>> {
+    let chains = dump(
+        libc::NFT_MSG_GETCHAIN,
+        table.family,
+        |header| unsafe {
+            // Kernels that support it only dump the chains of this table.
+            let chain = try_alloc!(sys::nftnl_chain_alloc());
+            sys::nftnl_chain_set_str(chain, sys::NFTNL_CHAIN_TABLE as u16, table.name.as_ptr());
+            sys::nftnl_chain_nlmsg_build_payload(header, chain);
+            sys::nftnl_chain_free(chain);
+        },
+        |header| unsafe { parse_chain(header) },
+    )?;
+    Ok(chains
+        .into_iter()
+        .filter(|chain| chain.table == table.name)
+        .collect())
+}
+
+/// Lists all rules in `chain`, in the order they are evaluated.
+pub fn list_rules(chain: &ChainInfo) -> io::Result<Vec<RuleInfo>> {
+    let rules = dump(
+        libc::NFT_MSG_GETRULE,
+        chain.family,
+        |header| unsafe {
+            // Kernels that support it only dump the rules of this chain.
+            let rule = try_alloc!(sys::nftnl_rule_alloc());
+            sys::nftnl_rule_set_str(rule, sys::NFTNL_RULE_TABLE as u16, chain.table.as_ptr());
+            sys::nftnl_rule_set_str(rule, sys::NFTNL_RULE_CHAIN as u16, chain.name.as_ptr());
+            sys::nftnl_rule_nlmsg_build_payload(header, rule);
+            sys::nftnl_rule_free(rule);
+        },
+        |header| unsafe { parse_rule(header) },
+    )?;
+    Ok(rules
+        .into_iter()
+        .filter(|rule| rule.table == chain.table && rule.chain == chain.name)
+        .collect())
+}
+
+/// Sends a dump request of type `msg_type` and runs `parse` on every object in the reply.
+/// Objects `parse` returns `None` for are left out.
+fn dump<T>(
+    msg_type: libc::c_int,
+    family: ProtoFamily,
+    build_payload: impl FnOnce(*mut libc::nlmsghdr),
+    mut parse: impl FnMut(&libc::nlmsghdr) -> io::Result<Option<T>>,
+) -> io::Result<Vec<T>> {
+    let socket = Socket::open()?;
+    let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+
+    let seq = 0;
+    let len = unsafe {
+        let header = sys::nftnl_nlmsg_build_hdr(
+            buffer.as_mut_ptr() as *mut c_char,
+            msg_type as u16,
+            family as u16,
+            libc::NLM_F_DUMP as u16,
+            seq,
+        );
+        build_payload(header);
+        (*header).nlmsg_len as usize
+    };
+    socket.send(&buffer[..len])?;
+
+    let mut objects = Vec::new();
+    loop {
+        for header in Messages::new(socket.recv(&mut buffer)?) {
+            match libc::c_int::from(header.nlmsg_type) {
+                libc::NLMSG_DONE => return Ok(objects),
+                libc::NLMSG_ERROR => {
+                    // The header is followed by the negated errno. Zero is an acknowledgement.
+                    let error = unsafe {
+                        *((header as *const libc::nlmsghdr).add(1) as *const libc::c_int)
+                    };
+                    if error != 0 {
+                        return Err(io::Error::from_raw_os_error(-error));
+                    }
+                }
+                _ => objects.extend(parse(header)?),
+            }
+        }
+    }
+}
+
+/// Parses a chain message, as sent in a chain dump. Returns `None` for chains of unknown
+/// families.
+pub(crate) unsafe fn parse_chain(header: &libc::nlmsghdr) -> io::Result<Option<ChainInfo>> {
+    let chain = try_alloc!(sys::nftnl_chain_alloc());
+    let result = if sys::nftnl_chain_nlmsg_parse(header, chain) < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        let is_set = |attr: u32| sys::nftnl_chain_is_set(chain, attr as u16);
+        Ok(parse_family(sys::nftnl_chain_get_u32(
+            chain,
+            sys::NFTNL_CHAIN_FAMILY as u16,
+        ))
+        .and_then(|family| {
+            Some(ChainInfo {
+                name: get_str(sys::nftnl_chain_get_str(
+                    chain,
+                    sys::NFTNL_CHAIN_NAME as u16,
+                ))?,
+                table: get_str(sys::nftnl_chain_get_str(
+                    chain,
+                    sys::NFTNL_CHAIN_TABLE as u16,
+                ))?,
+                family,
+                hook: if is_set(sys::NFTNL_CHAIN_HOOKNUM) {
+                    Hook::from_raw(sys::nftnl_chain_get_u32(
+                        chain,
+                        sys::NFTNL_CHAIN_HOOKNUM as u16,
+                    ))
+                } else {
+                    None
+                },
+                policy: if is_set(sys::NFTNL_CHAIN_POLICY) {
+                    Policy::from_raw(sys::nftnl_chain_get_u32(
+                        chain,
+                        sys::NFTNL_CHAIN_POLICY as u16,
+                    ))
+                } else {
+                    None
+                },
+                chain_type: get_str(sys::nftnl_chain_get_str(
+                    chain,
+                    sys::NFTNL_CHAIN_TYPE as u16,
+                ))
+                .and_then(|chain_type| ChainType::from_c_str(&chain_type)),
+            })
+        }))
+    };
+    sys::nftnl_chain_free(chain);
+    result
+}
+
+/// Parses a rule message, as sent in a rule dump. Returns `None` for rules of unknown families.
+pub(crate) unsafe fn parse_rule(header: &libc::nlmsghdr) -> io::Result<Option<RuleInfo>> {
+    let rule = try_alloc!(sys::nftnl_rule_alloc());
+    let result = if sys::nftnl_rule_nlmsg_parse(header, rule) < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(
+            parse_family(sys::nftnl_rule_get_u32(rule, sys::NFTNL_RULE_FAMILY as u16)).and_then(
+                |family| {
+                    Some(RuleInfo {
+                        table: get_str(sys::nftnl_rule_get_str(
+                            rule,
+                            sys::NFTNL_RULE_TABLE as u16,
+                        ))?,
+                        chain: get_str(sys::nftnl_rule_get_str(
+                            rule,
+                            sys::NFTNL_RULE_CHAIN as u16,
+                        ))?,
+                        family,
+                        handle: sys::nftnl_rule_get_u64(rule, sys::NFTNL_RULE_HANDLE as u16),
+                        position: sys::nftnl_rule_get_u64(rule, sys::NFTNL_RULE_POSITION as u16),
+                    })
+                },
+            ),
+        )
+    };
+    sys::nftnl_rule_free(rule);
+    result
+}
+
+fn parse_family(family: u32) -> Option<ProtoFamily> {
+    const FAMILIES: [ProtoFamily; 8] = [
+        ProtoFamily::Unspec,
+        ProtoFamily::Inet,
+        ProtoFamily::Ipv4,
+        ProtoFamily::Arp,
+        ProtoFamily::NetDev,
+        ProtoFamily::Bridge,
+        ProtoFamily::Ipv6,
+        ProtoFamily::DecNet,
+    ];
+    FAMILIES
+        .iter()
+        .find(|candidate| **candidate as u32 == family)
+        .copied()
+}
+
+pub(crate) unsafe fn get_str(ptr: *const c_char) -> Option<CString> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr).to_owned())
+    }
+}
@@ -1,16 +1,32 @@
-use crate::{chain::Chain, expr::Expression, MsgType};
+use crate::{
+    chain::Chain,
+    expr::{ExprError, Expression},
+    query::RuleInfo,
+    MsgType, ProtoFamily, Table,
+};
 use nftnl_sys::{self as sys, libc};
-use std::ffi::c_void;
+use std::ffi::{c_void, CStr};
+use std::mem;
 use std::os::raw::c_char;
 
+/// Userdata entry type for rule comments, `NFTNL_UDATA_RULE_COMMENT` in libnftnl.
+const UDATA_RULE_COMMENT: u8 = 0;
+/// Max length of a rule comment including the nul terminator, as enforced by the `nft` tool.
+const COMMENT_MAXLEN: usize = 128;
+// From linux/netfilter/nf_tables.h and linux/netlink.h. Not exposed by the libc crate.
+const NFTA_RULE_EXPRESSIONS: u16 = 4;
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
 /// A nftables firewall rule.
 pub struct Rule<'a> {
     rule: *mut sys::nftnl_rule,
     chain: &'a Chain<'a>,
+    echo: bool,
+    append: bool,
 }
 
-// Safety: It should be safe to pass this around and *read* from it
-// from multiple threads
+// Safety: libnftnl does not synchronize access to the object. All methods mutating it take
+// `&mut self`, so only reads can happen concurrently. See "Thread safety" in the crate docs.
 unsafe impl<'a> Send for Rule<'a> {}
 unsafe impl<'a> Sync for Rule<'a> {}
 
@@ -37,29 +53,169 @@ impl<'a> Rule<'a> {
                 chain.get_name().as_ptr(),
             );
 
-            Rule { rule, chain }
+            Rule {
+                rule,
+                chain,
+                echo: false,
+                append: true,
+            }
         }
     }
 
     /// Sets the position of this rule within the chain it lives in. By default a new rule is added
-    /// to the end of the chain.
+    /// to the end of the chain. The position is the handle of another rule in the chain, and this
+    /// rule is added after it. Same as [`set_position_after_handle`].
+    ///
+    /// [`set_position_after_handle`]: #method.set_position_after_handle
     pub fn set_position(&mut self, position: u64) {
+        self.set_position_after_handle(position);
+    }
+
+    /// Adds this rule right before the rule with handle `handle` in the chain.
+    pub fn set_position_before_handle(&mut self, handle: u64) {
+        unsafe {
+            sys::nftnl_rule_set_u64(self.rule, sys::NFTNL_RULE_POSITION as u16, handle);
+        }
+        self.append = false;
+    }
+
+    /// Adds this rule right after the rule with handle `handle` in the chain.
+    pub fn set_position_after_handle(&mut self, handle: u64) {
         unsafe {
-            sys::nftnl_rule_set_u64(self.rule, sys::NFTNL_RULE_POSITION as u16, position);
+            sys::nftnl_rule_set_u64(self.rule, sys::NFTNL_RULE_POSITION as u16, handle);
         }
+        self.append = true;
     }
 
+    /// Adds this rule to the beginning of the chain instead of the end. Clears any position set
+    /// before.
+    pub fn prepend(&mut self) {
+        unsafe {
+            sys::nftnl_rule_unset(self.rule, sys::NFTNL_RULE_POSITION as u16);
+        }
+        self.append = false;
+    }
+
+    /// Makes the kernel send the rule back when it is added or replaced, with its handle. Parse
+    /// the reply with [`get_rules_cb`] to learn the handle, which is needed to delete or replace
+    /// the rule later. The echoed rule has no position, so its [`RuleInfo::position`] is 0.
+    ///
+    /// [`get_rules_cb`]: fn.get_rules_cb.html
+    /// [`RuleInfo::position`]: query/struct.RuleInfo.html#structfield.position
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// Sets the handle of the rule in the kernel. Needed to delete the rule with `MsgType::Del`
+    /// or replace it with `MsgType::Replace`.
     pub fn set_handle(&mut self, handle: u64) {
         unsafe {
             sys::nftnl_rule_set_u64(self.rule, sys::NFTNL_RULE_HANDLE as u16, handle);
         }
     }
 
+    /// Sets the userdata of this rule. The kernel stores it with the rule without interpreting
+    /// it. The `nft` tool stores comments there, so replacing the userdata replaces any comment.
+    /// See [`set_comment`].
+    ///
+    /// [`set_comment`]: #method.set_comment
+    pub fn set_userdata(&mut self, data: &[u8]) {
+        unsafe {
+            sys::nftnl_rule_set_data(
+                self.rule,
+                sys::NFTNL_RULE_USERDATA as u16,
+                data.as_ptr() as *const c_void,
+                data.len() as u32,
+            );
+        }
+    }
+
+    /// Returns the userdata of this rule, or `None` if it has none.
+    pub fn get_userdata(&self) -> Option<&[u8]> {
+        unsafe {
+            if !sys::nftnl_rule_is_set(self.rule, sys::NFTNL_RULE_USERDATA as u16) {
+                return None;
+            }
+            let mut len = 0u32;
+            let ptr =
+                sys::nftnl_rule_get_data(self.rule, sys::NFTNL_RULE_USERDATA as u16, &mut len);
+            if ptr.is_null() {
+                return None;
+            }
+            Some(std::slice::from_raw_parts(ptr as *const u8, len as usize))
+        }
+    }
+
+    /// Sets a comment on this rule. The comment is stored in the rule userdata in the same format
+    /// as the `nft` tool uses, so it is shown by `nft list ruleset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `comment` is longer than 127 bytes or contains a nul byte.
+    pub fn set_comment(&mut self, comment: &str) {
+        assert!(
+            comment.len() < COMMENT_MAXLEN,
+            "Rule comment can be at most {} bytes",
+            COMMENT_MAXLEN - 1
+        );
+        assert!(!comment.contains('\0'), "Rule comment can not contain nul");
+        let mut userdata = Vec::with_capacity(comment.len() + 3);
+        userdata.push(UDATA_RULE_COMMENT);
+        userdata.push(comment.len() as u8 + 1);
+        userdata.extend_from_slice(comment.as_bytes());
+        userdata.push(0);
+        self.set_userdata(&userdata);
+    }
+
+    /// Returns the comment of this rule, as set by [`set_comment`] or the `nft` tool. Returns
+    /// `None` if the rule has no comment or if it is not valid UTF-8.
+    ///
+    /// [`set_comment`]: #method.set_comment
+    pub fn get_comment(&self) -> Option<&str> {
+        let mut userdata = self.get_userdata()?;
+        // The userdata is a list of type-length-value entries, with one byte type and length.
+        while userdata.len() >= 2 {
+            let (type_, len) = (userdata[0], userdata[1] as usize);
+            let value = userdata.get(2..2 + len)?;
+            if type_ == UDATA_RULE_COMMENT {
+                let value = value.strip_suffix(&[0]).unwrap_or(value);
+                return std::str::from_utf8(value).ok();
+            }
+            userdata = &userdata[2 + len..];
+        }
+        None
+    }
+
     /// Adds an expression to this rule. Expressions are evaluated from first to last added.
     /// As soon as an expression does not match the packet it's being evaluated for, evaluation
     /// stops and the packet is evaluated against the next rule in the chain.
-    pub fn add_expr(&mut self, expr: &impl Expression) {
-        unsafe { sys::nftnl_rule_add_expr(self.rule, expr.to_expr(self)) }
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expression can not be created, see [`try_add_expr`].
+    ///
+    /// [`try_add_expr`]: #method.try_add_expr
+    pub fn add_expr(&mut self, expr: &(impl Expression + ?Sized)) {
+        self.try_add_expr(expr)
+            .expect("Unable to add expression to rule");
+    }
+
+    /// Adds an expression to this rule, like [`add_expr`], but returns an error instead of
+    /// panicking if [`Expression::to_expr`] fails and returns a null pointer. All expressions in
+    /// this crate abort on allocation failure instead, so this only fails for invalid expressions,
+    /// such as a [`Queue`] with an unrepresentable queue range, or for expressions implemented
+    /// elsewhere.
+    ///
+    /// [`add_expr`]: #method.add_expr
+    /// [`Expression::to_expr`]: expr/trait.Expression.html#tymethod.to_expr
+    /// [`Queue`]: expr/struct.Queue.html
+    pub fn try_add_expr(&mut self, expr: &(impl Expression + ?Sized)) -> Result<(), ExprError> {
+        let raw_expr = expr.to_expr(self);
+        if raw_expr.is_null() {
+            return Err(ExprError::AllocationFailed);
+        }
+        unsafe { sys::nftnl_rule_add_expr(self.rule, raw_expr) };
+        Ok(())
     }
 
     /// Returns a reference to the [`Chain`] this rule lives in.
@@ -68,27 +224,155 @@ impl<'a> Rule<'a> {
     pub fn get_chain(&self) -> &Chain<'_> {
         self.chain
     }
+
+    /// Returns a reference to the [`Table`] the chain of this rule belongs to. Shorthand for
+    /// `rule.get_chain().get_table()`.
+    ///
+    /// [`Table`]: struct.Table.html
+    pub fn get_table(&self) -> &Table {
+        self.chain.get_table()
+    }
+
+    /// Returns the expressions of this rule as serialized in the netlink message, the payload of
+    /// the `NFTA_RULE_EXPRESSIONS` attribute. Rules with equal expressions give equal bytes.
+    pub(crate) fn serialized_exprs(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; crate::nft_nlmsg_maxsize() as usize];
+        unsafe { crate::NlMsg::write(self, buf.as_mut_ptr() as *mut c_void, 0, MsgType::Add) };
+        let msg_len = unsafe { (*(buf.as_ptr() as *const libc::nlmsghdr)).nlmsg_len } as usize;
+        // The attributes follow the netlink header and the four byte nfgenmsg header.
+        let attrs_start = mem::size_of::<libc::nlmsghdr>() + 4;
+        let mut attrs = &buf[attrs_start..msg_len];
+        while attrs.len() >= 4 {
+            let attr_len = u16::from_ne_bytes([attrs[0], attrs[1]]) as usize;
+            let attr_type = u16::from_ne_bytes([attrs[2], attrs[3]]) & NLA_TYPE_MASK;
+            if attr_len < 4 || attr_len > attrs.len() {
+                break;
+            }
+            if attr_type == NFTA_RULE_EXPRESSIONS {
+                return attrs[4..attr_len].to_vec();
+            }
+            attrs = attrs.get((attr_len + 3) & !3..).unwrap_or(&[]);
+        }
+        Vec::new()
+    }
 }
 
 unsafe impl<'a> crate::NlMsg for Rule<'a> {
     unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
         let type_ = match msg_type {
-            MsgType::Add => libc::NFT_MSG_NEWRULE,
+            MsgType::Add | MsgType::Replace => libc::NFT_MSG_NEWRULE,
             MsgType::Del => libc::NFT_MSG_DELRULE,
+            MsgType::Flush => panic!("Rules can not be flushed"),
         };
-        let flags: u16 = match msg_type {
-            MsgType::Add => (libc::NLM_F_CREATE | libc::NLM_F_APPEND | libc::NLM_F_EXCL) as u16,
-            MsgType::Del => 0u16,
-        };
+        // Without a position, NLM_F_APPEND decides between the end and the beginning of the chain.
+        // With one, it decides between after and before the rule at that position.
+        let mut flags: u16 = match msg_type {
+            MsgType::Add if self.append => {
+                (libc::NLM_F_CREATE | libc::NLM_F_APPEND | libc::NLM_F_EXCL) as u16
+            }
+            MsgType::Add => (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            MsgType::Replace => libc::NLM_F_REPLACE as u16,
+            _ => 0u16,
+        } | libc::NLM_F_ACK as u16;
+        if self.echo && msg_type != MsgType::Del {
+            flags |= libc::NLM_F_ECHO as u16;
+        }
         let header = sys::nftnl_nlmsg_build_hdr(
             buf as *mut c_char,
             type_ as u16,
-            self.chain.get_table().get_family() as u16,
+            self.get_table().get_family() as u16,
             flags,
             seq,
         );
         sys::nftnl_rule_nlmsg_build_payload(header, self.rule);
     }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        msg_type != MsgType::Flush
+    }
+}
+
+/// Returns a buffer containing a netlink message which requests a list of the rules in `chain`.
+/// Kernels that don't support filtering the dump list the rules of all chains in the family, so
+/// callers should check [`RuleInfo::table`] and [`RuleInfo::chain`] of the result.
+///
+/// [`RuleInfo::table`]: query/struct.RuleInfo.html#structfield.table
+/// [`RuleInfo::chain`]: query/struct.RuleInfo.html#structfield.chain
+pub fn get_rules_nlmsg(seq: u32, chain: &Chain<'_>) -> Vec<u8> {
+    let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+    unsafe {
+        let table = chain.get_table();
+        let header = sys::nftnl_nlmsg_build_hdr(
+            buffer.as_mut_ptr() as *mut c_char,
+            libc::NFT_MSG_GETRULE as u16,
+            table.get_family() as u16,
+            libc::NLM_F_DUMP as u16,
+            seq,
+        );
+        let rule = try_alloc!(sys::nftnl_rule_alloc());
+        sys::nftnl_rule_set_str(
+            rule,
+            sys::NFTNL_RULE_TABLE as u16,
+            table.get_name().as_ptr(),
+        );
+        sys::nftnl_rule_set_str(
+            rule,
+            sys::NFTNL_RULE_CHAIN as u16,
+            chain.get_name().as_ptr(),
+        );
+        sys::nftnl_rule_nlmsg_build_payload(header, rule);
+        sys::nftnl_rule_free(rule);
+    }
+    buffer
+}
+
+/// A callback to parse the response for messages created with `get_rules_nlmsg`, or the echo of
+/// rules added with [`Rule::set_echo`]. This callback appends the rules in the response to
+/// `rules`.
+///
+/// [`Rule::set_echo`]: struct.Rule.html#method.set_echo
+pub fn get_rules_cb(header: &libc::nlmsghdr, rules: &mut Vec<RuleInfo>) -> libc::c_int {
+    match unsafe { crate::query::parse_rule(header) } {
+        Ok(rule) => {
+            rules.extend(rule);
+            1
+        }
+        Err(err) => {
+            error!("Failed to parse netlink rule message - {}", err);
+            -1
+        }
+    }
+}
+
+/// Writes a message deleting all rules in `chain`, or all rules in all chains of `table` if
+/// `chain` is `None`. This is a rule deletion message without a rule handle.
+///
+/// Like `nft flush table`, flushing a table keeps its chains. The kernel can only delete chains
+/// by name or handle, and deleting them would fail anyway while other chains jump to them.
+/// `NLM_F_ACK` is used like for any other deletion: `NLM_F_ROOT` and `NLM_F_MATCH` only have a
+/// meaning for get requests, and the kernel ignores them on `NFT_MSG_DELRULE`.
+pub(crate) unsafe fn write_flush_msg(
+    buf: *mut c_void,
+    seq: u32,
+    family: ProtoFamily,
+    table: &CStr,
+    chain: Option<&CStr>,
+) {
+    let rule = try_alloc!(sys::nftnl_rule_alloc());
+    sys::nftnl_rule_set_u32(rule, sys::NFTNL_RULE_FAMILY as u16, family as u32);
+    sys::nftnl_rule_set_str(rule, sys::NFTNL_RULE_TABLE as u16, table.as_ptr());
+    if let Some(chain) = chain {
+        sys::nftnl_rule_set_str(rule, sys::NFTNL_RULE_CHAIN as u16, chain.as_ptr());
+    }
+    let header = sys::nftnl_nlmsg_build_hdr(
+        buf as *mut c_char,
+        libc::NFT_MSG_DELRULE as u16,
+        family as u16,
+        libc::NLM_F_ACK as u16,
+        seq,
+    );
+    sys::nftnl_rule_nlmsg_build_payload(header, rule);
+    sys::nftnl_rule_free(rule);
 }
 
 impl<'a> Drop for Rule<'a> {
@@ -1,13 +1,18 @@
-use crate::{table::Table, MsgType, ProtoFamily};
+use crate::{expr::Verdict, query::get_str, table::Table, MsgType, ProtoFamily};
 use nftnl_sys::{self as sys, libc};
 use std::{
     cell::Cell,
-    ffi::{c_void, CStr},
+    ffi::{c_void, CStr, CString},
     net::{Ipv4Addr, Ipv6Addr},
     os::raw::c_char,
     rc::Rc,
+    time::Duration,
 };
 
+/// The flags of the sets this crate creates by default. The set is bound to the rule using it and
+/// its elements can't change after creation.
+const ANONYMOUS_CONSTANT: u32 = (libc::NFT_SET_ANONYMOUS | libc::NFT_SET_CONSTANT) as u32;
+
 #[macro_export]
 macro_rules! nft_set {
     ($name:expr, $id:expr, $table:expr, $family:expr) => {
@@ -34,6 +39,31 @@ pub struct Set<'a, K> {
 
 impl<'a, K> Set<'a, K> {
     pub fn new(name: &CStr, id: u32, table: &'a Table, family: ProtoFamily) -> Self
+    where
+        K: SetKey,
+    {
+        Self::with_flags(name, id, table, family, ANONYMOUS_CONSTANT)
+    }
+
+    /// Creates a set whose elements are intervals of keys, such as CIDR prefixes or port
+    /// ranges. Elements are added with [`add_range`] rather than [`add`].
+    ///
+    /// [`add_range`]: #method.add_range
+    /// [`add`]: #method.add
+    pub fn with_interval(name: &CStr, id: u32, table: &'a Table, family: ProtoFamily) -> Self
+    where
+        K: SetKey,
+    {
+        Self::with_flags(
+            name,
+            id,
+            table,
+            family,
+            ANONYMOUS_CONSTANT | libc::NFT_SET_INTERVAL as u32,
+        )
+    }
+
+    fn with_flags(name: &CStr, id: u32, table: &'a Table, family: ProtoFamily, flags: u32) -> Self
     where
         K: SetKey,
     {
@@ -45,11 +75,7 @@ impl<'a, K> Set<'a, K> {
             sys::nftnl_set_set_str(set, sys::NFTNL_SET_NAME as u16, name.as_ptr());
             sys::nftnl_set_set_u32(set, sys::NFTNL_SET_ID as u16, id);
 
-            sys::nftnl_set_set_u32(
-                set,
-                sys::NFTNL_SET_FLAGS as u16,
-                (libc::NFT_SET_ANONYMOUS | libc::NFT_SET_CONSTANT) as u32,
-            );
+            sys::nftnl_set_set_u32(set, sys::NFTNL_SET_FLAGS as u16, flags);
             sys::nftnl_set_set_u32(set, sys::NFTNL_SET_KEY_TYPE as u16, K::TYPE);
             sys::nftnl_set_set_u32(set, sys::NFTNL_SET_KEY_LEN as u16, K::LEN);
 
@@ -67,21 +93,107 @@ impl<'a, K> Set<'a, K> {
         K: SetKey,
     {
         unsafe {
-            let elem = try_alloc!(sys::nftnl_set_elem_alloc());
+            let elem = Self::alloc_elem(key);
+            sys::nftnl_set_elem_add(self.set, elem);
+        }
+    }
 
-            let data = key.data();
-            let data_len = data.len() as u32;
-            trace!("Adding key {:?} with len {}", data, data_len);
-            sys::nftnl_set_elem_set(
+    /// Adds `key` to the set. With a `timeout` the element is removed again when the timeout
+    /// expires, overriding the default timeout of the set. This enables timeouts on the set,
+    /// see [`set_timeout`].
+    ///
+    /// [`set_timeout`]: #method.set_timeout
+    pub fn add_with_timeout(&mut self, key: &K, timeout: Option<Duration>)
+    where
+        K: SetKey,
+    {
+        unsafe {
+            let elem = Self::alloc_elem(key);
+            if let Some(timeout) = timeout {
+                self.add_flags(libc::NFT_SET_TIMEOUT as u32);
+                sys::nftnl_set_elem_set_u64(
+                    elem,
+                    sys::NFTNL_SET_ELEM_TIMEOUT as u16,
+                    duration_to_millis(timeout),
+                );
+            }
+            sys::nftnl_set_elem_add(self.set, elem);
+        }
+    }
+
+    /// Adds the interval from `start` up to, but not including, `end` to a set created with
+    /// [`with_interval`]. This is how the kernel represents intervals: an element at the first
+    /// key of the interval, and an element flagged as interval end at the first key after it.
+    /// `end` is `None` for an interval reaching the largest possible key.
+    ///
+    /// [`with_interval`]: #method.with_interval
+    pub fn add_range(&mut self, start: &K, end: Option<&K>)
+    where
+        K: SetKey,
+    {
+        unsafe {
+            sys::nftnl_set_elem_add(self.set, Self::alloc_elem(start));
+            if let Some(end) = end {
+                let elem = Self::alloc_elem(end);
+                sys::nftnl_set_elem_set_u32(
+                    elem,
+                    sys::NFTNL_SET_ELEM_FLAGS as u16,
+                    libc::NFT_SET_ELEM_INTERVAL_END as u32,
+                );
+                sys::nftnl_set_elem_add(self.set, elem);
+            }
+        }
+    }
+
+    /// Adds an element mapping `key` to `verdict`. Used to build verdict maps, where a lookup
+    /// in the set decides what to do with the packet. The set must have been created as a map,
+    /// see [`VerdictMap`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `verdict` is `Verdict::Reject`. Rejecting is not a verdict code and can't be
+    /// stored in a map.
+    ///
+    /// [`VerdictMap`]: struct.VerdictMap.html
+    pub fn add_with_verdict(&mut self, key: &K, verdict: &Verdict)
+    where
+        K: SetKey,
+    {
+        let raw_verdict = verdict
+            .to_raw_verdict()
+            .expect("Reject can not be used as set element data");
+        unsafe {
+            let elem = Self::alloc_elem(key);
+            sys::nftnl_set_elem_set_u32(
                 elem,
-                sys::NFTNL_SET_ELEM_KEY as u16,
-                data.as_ref() as *const _ as *const c_void,
-                data_len,
+                sys::NFTNL_SET_ELEM_VERDICT as u16,
+                raw_verdict as u32,
             );
+            if let Some(chain) = verdict.chain() {
+                sys::nftnl_set_elem_set_str(elem, sys::NFTNL_SET_ELEM_CHAIN as u16, chain.as_ptr());
+            }
             sys::nftnl_set_elem_add(self.set, elem);
         }
     }
 
+    unsafe fn alloc_elem(key: &K) -> *mut sys::nftnl_set_elem
+    where
+        K: SetKey,
+    {
+        let elem = try_alloc!(sys::nftnl_set_elem_alloc());
+
+        let data = key.data();
+        let data_len = data.len() as u32;
+        trace!("Adding key {:?} with len {}", data, data_len);
+        sys::nftnl_set_elem_set(
+            elem,
+            sys::NFTNL_SET_ELEM_KEY as u16,
+            data.as_ref() as *const _ as *const c_void,
+            data_len,
+        );
+        elem
+    }
+
     pub fn elems_iter(&'a self) -> SetElemsIter<'a, K> {
         SetElemsIter::new(self)
     }
@@ -101,9 +213,352 @@ impl<'a, K> Set<'a, K> {
         }
     }
 
+    /// Returns the name of this set as a `String`, replacing invalid UTF-8 with `U+FFFD`. For
+    /// logging and other places where the exact bytes don't matter.
+    pub fn get_name_string(&self) -> String {
+        self.get_name().to_string_lossy().into_owned()
+    }
+
     pub fn get_id(&self) -> u32 {
         unsafe { sys::nftnl_set_get_u32(self.set, sys::NFTNL_SET_ID as u16) }
     }
+
+    /// Returns the `NFT_SET_*` flags of this set.
+    pub fn get_flags(&self) -> u32 {
+        unsafe { sys::nftnl_set_get_u32(self.set, sys::NFTNL_SET_FLAGS as u16) }
+    }
+
+    /// Sets the default timeout of the elements in this set, and enables the `NFT_SET_TIMEOUT`
+    /// flag. Elements are removed when their timeout expires. The kernel keeps the timeout in
+    /// milliseconds, so it is rounded down to whole milliseconds.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.add_flags(libc::NFT_SET_TIMEOUT as u32);
+        unsafe {
+            sys::nftnl_set_set_u64(
+                self.set,
+                sys::NFTNL_SET_TIMEOUT as u16,
+                duration_to_millis(timeout),
+            );
+        }
+    }
+
+    /// Sets how often the kernel looks for and removes expired elements. Only has an effect
+    /// together with timeouts. Rounded down to whole milliseconds, and saturates at
+    /// `u32::MAX` milliseconds.
+    pub fn set_gc_interval(&mut self, interval: Duration) {
+        let millis = duration_to_millis(interval).min(u64::from(u32::MAX)) as u32;
+        unsafe {
+            sys::nftnl_set_set_u32(self.set, sys::NFTNL_SET_GC_INTERVAL as u16, millis);
+        }
+    }
+
+    /// Limits the set to at most `max_elements` elements. Adding elements to a full set fails
+    /// with `ENFILE`, both from a batch and from the packet path of a [`DynamicSet`]. In the
+    /// latter case the [`SetUpdate`] expression does not match. Expired elements of a set with
+    /// timeouts stop counting against the limit once they are garbage collected.
+    ///
+    /// [`DynamicSet`]: struct.DynamicSet.html
+    /// [`SetUpdate`]: ../expr/struct.SetUpdate.html
+    pub fn set_size(&mut self, max_elements: u32) {
+        unsafe {
+            sys::nftnl_set_set_u32(self.set, sys::NFTNL_SET_DESC_SIZE as u16, max_elements);
+        }
+    }
+
+    /// Returns the maximum number of elements of this set. `None` if the size is not limited.
+    pub fn get_size(&self) -> Option<u32> {
+        unsafe {
+            if sys::nftnl_set_is_set(self.set, sys::NFTNL_SET_DESC_SIZE as u16) {
+                Some(sys::nftnl_set_get_u32(
+                    self.set,
+                    sys::NFTNL_SET_DESC_SIZE as u16,
+                ))
+            } else {
+                None
+            }
+        }
+    }
+
+    fn add_flags(&mut self, flags: u32) {
+        let flags = self.get_flags() | flags;
+        unsafe { sys::nftnl_set_set_u32(self.set, sys::NFTNL_SET_FLAGS as u16, flags) };
+    }
+
+    /// Returns the default timeout, in milliseconds, of the elements in this set. `None` if no
+    /// timeout is set.
+    pub fn get_timeout(&self) -> Option<u64> {
+        unsafe {
+            if sys::nftnl_set_is_set(self.set, sys::NFTNL_SET_TIMEOUT as u16) {
+                Some(sys::nftnl_set_get_u64(
+                    self.set,
+                    sys::NFTNL_SET_TIMEOUT as u16,
+                ))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns the garbage collection interval, in milliseconds, of this set. `None` if no
+    /// interval is set.
+    pub fn get_gc_interval(&self) -> Option<u32> {
+        unsafe {
+            if sys::nftnl_set_is_set(self.set, sys::NFTNL_SET_GC_INTERVAL as u16) {
+                Some(sys::nftnl_set_get_u32(
+                    self.set,
+                    sys::NFTNL_SET_GC_INTERVAL as u16,
+                ))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns true if this is an anonymous set, bound to the rule using it.
+    pub fn is_anonymous(&self) -> bool {
+        self.get_flags() & libc::NFT_SET_ANONYMOUS as u32 != 0
+    }
+
+    /// Returns true if the elements of this set are intervals.
+    pub fn is_interval(&self) -> bool {
+        self.get_flags() & libc::NFT_SET_INTERVAL as u32 != 0
+    }
+}
+
+#[cfg(feature = "ipnetwork")]
+impl<'a> Set<'a, Ipv4Addr> {
+    /// Adds all addresses in `network` to a set created with [`with_interval`].
+    ///
+    /// Requires the `ipnetwork` feature.
+    ///
+    /// [`with_interval`]: #method.with_interval
+    pub fn add_network(&mut self, network: &ipnetwork::Ipv4Network) {
+        let end = u32::from(network.broadcast())
+            .checked_add(1)
+            .map(Ipv4Addr::from);
+        self.add_range(&network.network(), end.as_ref());
+    }
+}
+
+#[cfg(feature = "ipnetwork")]
+impl<'a> Set<'a, Ipv6Addr> {
+    /// Adds all addresses in `network` to a set created with [`with_interval`].
+    ///
+    /// Requires the `ipnetwork` feature.
+    ///
+    /// [`with_interval`]: #method.with_interval
+    pub fn add_network(&mut self, network: &ipnetwork::Ipv6Network) {
+        let host_mask = u128::MAX
+            .checked_shr(u32::from(network.prefix()))
+            .unwrap_or(0);
+        let last = u128::from(network.network()) | host_mask;
+        let end = last.checked_add(1).map(Ipv6Addr::from);
+        self.add_range(&network.network(), end.as_ref());
+    }
+}
+
+/// A named set the packet path can add elements to, or delete them from, with a [`SetUpdate`]
+/// expression. `flags dynamic` in `nft`. For example a set of the source addresses of packets
+/// that broke some rule, that a later rule drops all traffic from.
+///
+/// Unlike the other sets this crate creates, a dynamic set is not anonymous. It must have a
+/// unique name in its table and it outlives the rules using it, until it is deleted with a
+/// [`MsgType::Del`] message.
+///
+/// [`SetUpdate`]: ../expr/struct.SetUpdate.html
+/// [`MsgType::Del`]: ../enum.MsgType.html#variant.Del
+pub struct DynamicSet<'a, K> {
+    set: Set<'a, K>,
+}
+
+impl<'a, K: SetKey> DynamicSet<'a, K> {
+    pub fn new(name: &CStr, id: u32, table: &'a Table, family: ProtoFamily) -> Self {
+        let set = Set::with_flags(name, id, table, family, libc::NFT_SET_EVAL as u32);
+        DynamicSet { set }
+    }
+
+    /// Adds an initial element to the set.
+    pub fn add(&mut self, key: &K) {
+        self.set.add(key);
+    }
+}
+
+impl<'a, K> DynamicSet<'a, K> {
+    /// Returns the underlying set, for example to iterate over the element messages with
+    /// [`Set::elems_iter`].
+    ///
+    /// [`Set::elems_iter`]: struct.Set.html#method.elems_iter
+    pub fn as_set(&self) -> &Set<'a, K> {
+        &self.set
+    }
+
+    /// Returns the underlying set mutably, for example to give the elements a timeout with
+    /// [`Set::set_timeout`].
+    ///
+    /// [`Set::set_timeout`]: struct.Set.html#method.set_timeout
+    pub fn as_set_mut(&mut self) -> &mut Set<'a, K> {
+        &mut self.set
+    }
+}
+
+unsafe impl<'a, K> crate::NlMsg for DynamicSet<'a, K> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        self.set.write(buf, seq, msg_type)
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        self.set.supports_msg_type(msg_type)
+    }
+}
+
+/// A map from keys to verdicts, `vmap` in `nft`. Looked up with a [`VmapLookup`] expression,
+/// which applies the verdict of the matching element to the packet.
+///
+/// [`VmapLookup`]: ../expr/struct.VmapLookup.html
+pub struct VerdictMap<'a, K> {
+    set: Set<'a, K>,
+}
+
+impl<'a, K: SetKey> VerdictMap<'a, K> {
+    pub fn new(name: &CStr, id: u32, table: &'a Table, family: ProtoFamily) -> Self {
+        let set = Set::with_flags(
+            name,
+            id,
+            table,
+            family,
+            ANONYMOUS_CONSTANT | libc::NFT_SET_MAP as u32,
+        );
+        unsafe {
+            sys::nftnl_set_set_u32(
+                set.set,
+                sys::NFTNL_SET_DATA_TYPE as u16,
+                libc::NFT_DATA_VERDICT,
+            );
+        }
+        VerdictMap { set }
+    }
+
+    /// Adds an element mapping `key` to `verdict`. See [`Set::add_with_verdict`].
+    ///
+    /// [`Set::add_with_verdict`]: struct.Set.html#method.add_with_verdict
+    pub fn add_entry(&mut self, key: &K, verdict: &Verdict) {
+        self.set.add_with_verdict(key, verdict);
+    }
+}
+
+impl<'a, K> VerdictMap<'a, K> {
+    /// Returns the underlying set, for example to iterate over the element messages with
+    /// [`Set::elems_iter`].
+    ///
+    /// [`Set::elems_iter`]: struct.Set.html#method.elems_iter
+    pub fn as_set(&self) -> &Set<'a, K> {
+        &self.set
+    }
+}
+
+unsafe impl<'a, K> crate::NlMsg for VerdictMap<'a, K> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        self.set.write(buf, seq, msg_type)
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        self.set.supports_msg_type(msg_type)
+    }
+}
+
+/// A map from keys to data, `map` in `nft`. Looked up with a [`DataLookup`] expression, which
+/// loads the data of the matching element into a register. For example a map from source
+/// address to packet mark.
+///
+/// [`DataLookup`]: ../expr/struct.DataLookup.html
+pub struct DataMap<'a, K, V> {
+    set: Set<'a, K>,
+    _marker: ::std::marker::PhantomData<V>,
+}
+
+impl<'a, K: SetKey, V: MapValue> DataMap<'a, K, V> {
+    pub fn new(name: &CStr, id: u32, table: &'a Table, family: ProtoFamily) -> Self {
+        let set = Set::with_flags(
+            name,
+            id,
+            table,
+            family,
+            ANONYMOUS_CONSTANT | libc::NFT_SET_MAP as u32,
+        );
+        unsafe {
+            sys::nftnl_set_set_u32(set.set, sys::NFTNL_SET_DATA_TYPE as u16, V::TYPE);
+            sys::nftnl_set_set_u32(set.set, sys::NFTNL_SET_DATA_LEN as u16, V::DATA_LEN);
+        }
+        DataMap {
+            set,
+            _marker: ::std::marker::PhantomData,
+        }
+    }
+
+    /// Adds an element mapping `key` to `value`.
+    pub fn add_entry(&mut self, key: &K, value: &V) {
+        unsafe {
+            let elem = Set::alloc_elem(key);
+            let data = value.data();
+            sys::nftnl_set_elem_set(
+                elem,
+                sys::NFTNL_SET_ELEM_DATA as u16,
+                data.as_ref() as *const _ as *const c_void,
+                data.len() as u32,
+            );
+            sys::nftnl_set_elem_add(self.set.set, elem);
+        }
+    }
+}
+
+impl<'a, K, V> DataMap<'a, K, V> {
+    /// Returns the underlying set, for example to iterate over the element messages with
+    /// [`Set::elems_iter`].
+    ///
+    /// [`Set::elems_iter`]: struct.Set.html#method.elems_iter
+    pub fn as_set(&self) -> &Set<'a, K> {
+        &self.set
+    }
+}
+
+unsafe impl<'a, K, V> crate::NlMsg for DataMap<'a, K, V> {
+    unsafe fn write(&self, buf: *mut c_void, seq: u32, msg_type: MsgType) {
+        self.set.write(buf, seq, msg_type)
+    }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        self.set.supports_msg_type(msg_type)
+    }
+}
+
+/// Key type of a [`Set`] whose key type is not known, such as a set parsed from a kernel
+/// response. Does not implement [`SetKey`], so no elements can be added to such a set.
+///
+/// [`Set`]: struct.Set.html
+/// [`SetKey`]: trait.SetKey.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Opaque;
+
+impl<'a> Set<'a, Opaque> {
+    /// Wraps a set allocated by libnftnl, for example one filled in by `nftnl_set_nlmsg_parse`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be a valid set allocated with `nftnl_set_alloc`. The returned `Set` takes
+    /// ownership of it and frees it when dropped, so it must not be used or freed elsewhere
+    /// afterwards.
+    pub unsafe fn from_raw_ptr(
+        ptr: *mut sys::nftnl_set,
+        table: &'a Table,
+        family: ProtoFamily,
+    ) -> Self {
+        Set {
+            set: ptr,
+            table,
+            family,
+            _marker: ::std::marker::PhantomData,
+        }
+    }
 }
 
 unsafe impl<'a, K> crate::NlMsg for Set<'a, K> {
@@ -111,6 +566,8 @@ unsafe impl<'a, K> crate::NlMsg for Set<'a, K> {
         let type_ = match msg_type {
             MsgType::Add => libc::NFT_MSG_NEWSET,
             MsgType::Del => libc::NFT_MSG_DELSET,
+            MsgType::Flush => return write_flush_msg(buf, seq, self),
+            MsgType::Replace => panic!("Sets can not be replaced"),
         };
         let header = sys::nftnl_nlmsg_build_hdr(
             buf as *mut c_char,
@@ -121,6 +578,10 @@ unsafe impl<'a, K> crate::NlMsg for Set<'a, K> {
         );
         sys::nftnl_set_nlmsg_build_payload(header, self.set);
     }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        msg_type != MsgType::Replace
+    }
 }
 
 impl<'a, K> Drop for Set<'a, K> {
@@ -185,6 +646,8 @@ unsafe impl<'a, K> crate::NlMsg for SetElemsMsg<'a, K> {
                 libc::NLM_F_CREATE | libc::NLM_F_EXCL | libc::NLM_F_ACK,
             ),
             MsgType::Del => (libc::NFT_MSG_DELSETELEM, libc::NLM_F_ACK),
+            MsgType::Flush => return write_flush_msg(buf, seq, self.set),
+            MsgType::Replace => panic!("Set elements can not be replaced"),
         };
         let header = sys::nftnl_nlmsg_build_hdr(
             buf as *mut c_char,
@@ -197,6 +660,161 @@ unsafe impl<'a, K> crate::NlMsg for SetElemsMsg<'a, K> {
             header, self.iter,
         ));
     }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        msg_type != MsgType::Replace
+    }
+}
+
+fn duration_to_millis(duration: Duration) -> u64 {
+    duration.as_millis().min(u128::from(u64::MAX)) as u64
+}
+
+/// Writes a message deleting all elements in `set`. This is an element deletion message without
+/// any elements.
+unsafe fn write_flush_msg<K>(buf: *mut c_void, seq: u32, set: &Set<'_, K>) {
+    let flush_set = alloc_set_ref(set);
+    let header = sys::nftnl_nlmsg_build_hdr(
+        buf as *mut c_char,
+        libc::NFT_MSG_DELSETELEM as u16,
+        set.table.get_family() as u16,
+        libc::NLM_F_ACK as u16,
+        seq,
+    );
+    sys::nftnl_set_elems_nlmsg_build_payload(header, flush_set);
+    sys::nftnl_set_free(flush_set);
+}
+
+/// Allocates a set with only the family, table and name of `set`, for element messages that
+/// must not contain the elements of `set`.
+unsafe fn alloc_set_ref<K>(set: &Set<'_, K>) -> *mut sys::nftnl_set {
+    let set_ref = try_alloc!(sys::nftnl_set_alloc());
+    sys::nftnl_set_set_u32(
+        set_ref,
+        sys::NFTNL_SET_FAMILY as u16,
+        set.get_family() as u32,
+    );
+    sys::nftnl_set_set_str(
+        set_ref,
+        sys::NFTNL_SET_TABLE as u16,
+        set.table.get_name().as_ptr(),
+    );
+    sys::nftnl_set_set_str(set_ref, sys::NFTNL_SET_NAME as u16, set.get_name().as_ptr());
+    set_ref
+}
+
+/// A set loaded in the kernel, as collected by [`get_sets_cb`].
+///
+/// [`get_sets_cb`]: fn.get_sets_cb.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SetInfo {
+    pub name: CString,
+    /// Name of the table the set belongs to.
+    pub table: CString,
+    /// The `NFT_SET_*` flags of the set.
+    pub flags: u32,
+    /// The data type of the keys, such as 7 for IPv4 addresses. See [`SetKey::TYPE`].
+    ///
+    /// [`SetKey::TYPE`]: trait.SetKey.html#associatedconstant.TYPE
+    pub key_type: u32,
+    /// The length of the keys in bytes.
+    pub key_len: u32,
+}
+
+/// Returns a buffer containing a netlink message which requests a list of the sets in `table`.
+pub fn get_sets_nlmsg(seq: u32, table: &Table) -> Vec<u8> {
+    let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+    unsafe {
+        let header = sys::nftnl_nlmsg_build_hdr(
+            buffer.as_mut_ptr() as *mut c_char,
+            libc::NFT_MSG_GETSET as u16,
+            table.get_family() as u16,
+            libc::NLM_F_DUMP as u16,
+            seq,
+        );
+        let set = try_alloc!(sys::nftnl_set_alloc());
+        sys::nftnl_set_set_str(set, sys::NFTNL_SET_TABLE as u16, table.get_name().as_ptr());
+        sys::nftnl_set_nlmsg_build_payload(header, set);
+        sys::nftnl_set_free(set);
+    }
+    buffer
+}
+
+/// A callback to parse the response for messages created with `get_sets_nlmsg`. This callback
+/// appends the sets in the response to `sets`. Sets missing their name or table are skipped.
+pub fn get_sets_cb(header: &libc::nlmsghdr, sets: &mut Vec<SetInfo>) -> libc::c_int {
+    unsafe {
+        let set = try_alloc!(sys::nftnl_set_alloc());
+        let err = sys::nftnl_set_nlmsg_parse(header, set);
+        if err < 0 {
+            error!("Failed to parse netlink set message - {}", err);
+            sys::nftnl_set_free(set);
+            return err;
+        }
+        let name = get_str(sys::nftnl_set_get_str(set, sys::NFTNL_SET_NAME as u16));
+        let table = get_str(sys::nftnl_set_get_str(set, sys::NFTNL_SET_TABLE as u16));
+        if let (Some(name), Some(table)) = (name, table) {
+            sets.push(SetInfo {
+                name,
+                table,
+                flags: sys::nftnl_set_get_u32(set, sys::NFTNL_SET_FLAGS as u16),
+                key_type: sys::nftnl_set_get_u32(set, sys::NFTNL_SET_KEY_TYPE as u16),
+                key_len: sys::nftnl_set_get_u32(set, sys::NFTNL_SET_KEY_LEN as u16),
+            });
+        }
+        sys::nftnl_set_free(set);
+    }
+    1
+}
+
+/// Returns a buffer containing a netlink message which requests a list of the elements of the
+/// set with the same table and name as `set` in the kernel. The elements of `set` itself are not
+/// sent.
+pub fn get_set_elements_nlmsg<K>(seq: u32, set: &Set<'_, K>) -> Vec<u8> {
+    let mut buffer = vec![0; crate::nft_nlmsg_maxsize() as usize];
+    unsafe {
+        let header = sys::nftnl_nlmsg_build_hdr(
+            buffer.as_mut_ptr() as *mut c_char,
+            libc::NFT_MSG_GETSETELEM as u16,
+            set.table.get_family() as u16,
+            libc::NLM_F_DUMP as u16,
+            seq,
+        );
+        let set_ref = alloc_set_ref(set);
+        sys::nftnl_set_elems_nlmsg_build_payload(header, set_ref);
+        sys::nftnl_set_free(set_ref);
+    }
+    buffer
+}
+
+/// A callback to parse the response for messages created with `get_set_elements_nlmsg`. This
+/// callback appends the key of every element in the response to `keys`, in the same byte format
+/// as [`SetKey::data`]. Interval sets also contain the end elements of the intervals.
+///
+/// [`SetKey::data`]: trait.SetKey.html#tymethod.data
+pub fn get_set_elements_cb(header: &libc::nlmsghdr, keys: &mut Vec<Box<[u8]>>) -> libc::c_int {
+    unsafe {
+        let set = try_alloc!(sys::nftnl_set_alloc());
+        let err = sys::nftnl_set_elems_nlmsg_parse(header, set);
+        if err < 0 {
+            error!("Failed to parse netlink set element message - {}", err);
+            sys::nftnl_set_free(set);
+            return err;
+        }
+        let iter = try_alloc!(sys::nftnl_set_elems_iter_create(set));
+        let mut elem = sys::nftnl_set_elems_iter_next(iter);
+        while !elem.is_null() {
+            let mut len = 0u32;
+            let key = sys::nftnl_set_elem_get(elem, sys::NFTNL_SET_ELEM_KEY as u16, &mut len);
+            if !key.is_null() {
+                keys.push(std::slice::from_raw_parts(key as *const u8, len as usize).into());
+            }
+            elem = sys::nftnl_set_elems_iter_next(iter);
+        }
+        sys::nftnl_set_elems_iter_destroy(iter);
+        sys::nftnl_set_free(set);
+    }
+    1
 }
 
 pub trait SetKey {
@@ -223,3 +841,43 @@ impl SetKey for Ipv6Addr {
         self.octets().to_vec().into_boxed_slice()
     }
 }
+
+/// Type of the data in a [`DataMap`]. Like [`SetKey`], but for the data the keys map to.
+///
+/// [`DataMap`]: struct.DataMap.html
+/// [`SetKey`]: trait.SetKey.html
+pub trait MapValue {
+    /// The `nft` datatype of the value, used when listing the map.
+    const TYPE: u32;
+    const DATA_LEN: u32;
+
+    fn data(&self) -> Box<[u8]>;
+}
+
+/// A packet or connection mark, in host byte order like the kernel keeps marks.
+impl MapValue for u32 {
+    const TYPE: u32 = 19;
+    const DATA_LEN: u32 = 4;
+
+    fn data(&self) -> Box<[u8]> {
+        self.to_ne_bytes().to_vec().into_boxed_slice()
+    }
+}
+
+impl MapValue for Ipv4Addr {
+    const TYPE: u32 = 7;
+    const DATA_LEN: u32 = 4;
+
+    fn data(&self) -> Box<[u8]> {
+        self.octets().to_vec().into_boxed_slice()
+    }
+}
+
+impl MapValue for Ipv6Addr {
+    const TYPE: u32 = 8;
+    const DATA_LEN: u32 = 16;
+
+    fn data(&self) -> Box<[u8]> {
+        self.octets().to_vec().into_boxed_slice()
+    }
+}
@@ -6,6 +6,9 @@ use std::{
     os::raw::c_char,
 };
 
+// From linux/netfilter/nf_tables.h. Not exposed by the libc crate.
+const NFT_TABLE_F_DORMANT: u32 = 1;
+
 /// Abstraction of `nftnl_table`. The top level container in netfilter. A table has a protocol
 /// family and contain [`Chain`]s that in turn hold the rules.
 ///
@@ -41,6 +44,35 @@ impl Table {
         }
     }
 
+    /// Returns the name of this table as a `String`, replacing invalid UTF-8 with `U+FFFD`. For
+    /// logging and other places where the exact bytes don't matter.
+    pub fn get_name_string(&self) -> String {
+        self.get_name().to_string_lossy().into_owned()
+    }
+
+    /// Sets whether this table is dormant. The chains of a dormant table are not attached to
+    /// their hooks, so none of its rules see any packets, but the table and its content stay
+    /// loaded. Adding the table again with `dormant` set to `false` activates it.
+    pub fn set_dormant(&mut self, dormant: bool) {
+        unsafe {
+            let mut flags = sys::nftnl_table_get_u32(self.table, sys::NFTNL_TABLE_FLAGS as u16);
+            if dormant {
+                flags |= NFT_TABLE_F_DORMANT;
+            } else {
+                flags &= !NFT_TABLE_F_DORMANT;
+            }
+            sys::nftnl_table_set_u32(self.table, sys::NFTNL_TABLE_FLAGS as u16, flags);
+        }
+    }
+
+    /// Returns whether this table is dormant. See [`set_dormant`].
+    ///
+    /// [`set_dormant`]: #method.set_dormant
+    pub fn is_dormant(&self) -> bool {
+        let flags = unsafe { sys::nftnl_table_get_u32(self.table, sys::NFTNL_TABLE_FLAGS as u16) };
+        flags & NFT_TABLE_F_DORMANT != 0
+    }
+
     /// Returns the protocol family for this table.
     pub fn get_family(&self) -> ProtoFamily {
         self.family
@@ -52,6 +84,10 @@ unsafe impl crate::NlMsg for Table {
         let raw_msg_type = match msg_type {
             MsgType::Add => libc::NFT_MSG_NEWTABLE,
             MsgType::Del => libc::NFT_MSG_DELTABLE,
+            MsgType::Flush => {
+                return crate::rule::write_flush_msg(buf, seq, self.family, self.get_name(), None);
+            }
+            MsgType::Replace => panic!("Tables can not be replaced"),
         };
         let header = sys::nftnl_nlmsg_build_hdr(
             buf as *mut c_char,
@@ -62,6 +98,10 @@ unsafe impl crate::NlMsg for Table {
         );
         sys::nftnl_table_nlmsg_build_payload(header, self.table);
     }
+
+    fn supports_msg_type(&self, msg_type: MsgType) -> bool {
+        msg_type != MsgType::Replace
+    }
 }
 
 impl Drop for Table {
@@ -0,0 +1,78 @@
+//! Common value types used when matching on packet data.
+
+use crate::expr::ToSlice;
+use std::{borrow::Cow, fmt, str::FromStr};
+
+/// A 48 bit Ethernet MAC address. Can be compared against the link layer addresses loaded by
+/// [`LLHeaderField::Daddr`] and [`LLHeaderField::Saddr`].
+///
+/// Formats and parses as six colon separated hexadecimal octets, `01:23:45:67:89:ab`.
+///
+/// [`LLHeaderField::Daddr`]: ../expr/enum.LLHeaderField.html#variant.Daddr
+/// [`LLHeaderField::Saddr`]: ../expr/enum.LLHeaderField.html#variant.Saddr
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    /// Returns the six octets making up this address.
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let o = &self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            o[0], o[1], o[2], o[3], o[4], o[5]
+        )
+    }
+}
+
+/// Error returned when parsing a [`MacAddr`] from a string fails.
+///
+/// [`MacAddr`]: struct.MacAddr.html
+#[derive(Debug)]
+pub struct ParseMacAddrError(());
+
+impl fmt::Display for ParseMacAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "Invalid MAC address syntax".fmt(f)
+    }
+}
+
+impl std::error::Error for ParseMacAddrError {}
+
+impl FromStr for MacAddr {
+    type Err = ParseMacAddrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut octets = [0u8; 6];
+        let mut parts = s.split(':');
+        for octet in octets.iter_mut() {
+            let part = parts.next().ok_or(ParseMacAddrError(()))?;
+            if part.len() != 2 {
+                return Err(ParseMacAddrError(()));
+            }
+            *octet = u8::from_str_radix(part, 16).map_err(|_| ParseMacAddrError(()))?;
+        }
+        if parts.next().is_some() {
+            return Err(ParseMacAddrError(()));
+        }
+        Ok(MacAddr(octets))
+    }
+}
+
+impl ToSlice for MacAddr {
+    fn to_slice(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+}
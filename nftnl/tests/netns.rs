@@ -0,0 +1,745 @@
+//! Integration tests installing real rules into netfilter and verifying their effect on traffic.
+//!
+//! Every test moves its own thread into a fresh network namespace with
+//! `unshare(CLONE_NEWNET)`, brings up the loopback interface and installs an `ip` family table
+//! there. This way the tests never touch the firewall of the host and can run in parallel. Test
+//! traffic is UDP sent over the loopback interface inside the namespace.
+//!
+//! Only compiled with the `integration-tests` feature. Creating network namespaces and
+//! modifying netfilter requires the `CAP_NET_ADMIN` capability, so the tests must be run as root
+//! (or with that capability granted), and the `ip` binary from iproute2 must be in `PATH`:
+//! ```bash
+//! # cargo test --features integration-tests --test netns
+//! ```
+#![cfg(feature = "integration-tests")]
+
+use nftnl::{
+    expr::{Nat, NatFlags, NatType, Register, States},
+    nft_expr,
+    nftnl_sys::libc,
+    query::RuleInfo,
+    set::DynamicSet,
+    Batch, Chain, ChainType, FinalizedBatch, Hook, MsgType, Policy, ProtoFamily, Rule, Table,
+};
+use nix::sched::{unshare, CloneFlags};
+use std::{
+    ffi::CString,
+    io,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    panic::{self, AssertUnwindSafe},
+    process::Command,
+    time::Duration,
+};
+
+const TABLE_NAME: &str = "nftnl-test";
+const RECV_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Moves the current thread into a new, empty, network namespace and brings up loopback.
+fn enter_netns() {
+    unshare(CloneFlags::CLONE_NEWNET)
+        .expect("Unable to create network namespace. Is CAP_NET_ADMIN granted?");
+    let status = Command::new("ip")
+        .args(["link", "set", "lo", "up"])
+        .status()
+        .expect("Unable to run ip");
+    assert!(status.success(), "Unable to bring up loopback");
+}
+
+fn table() -> Table {
+    Table::new(&CString::new(TABLE_NAME).unwrap(), ProtoFamily::Ipv4)
+}
+
+fn base_chain<'a>(table: &'a Table, name: &str, hook: Hook, policy: Policy) -> Chain<'a> {
+    let mut chain = Chain::new(&CString::new(name).unwrap(), table);
+    chain.set_hook(hook, 0);
+    chain.set_policy(policy);
+    chain
+}
+
+fn add_udp_dport_match(rule: &mut Rule<'_>, port: u16) {
+    rule.add_expr(&nft_expr!(meta l4proto));
+    rule.add_expr(&nft_expr!(cmp == libc::IPPROTO_UDP as u8));
+    rule.add_expr(&nft_expr!(payload udp dport));
+    rule.add_expr(&nft_expr!(cmp == port.to_be()));
+}
+
+fn send_and_process(batch: &FinalizedBatch) -> io::Result<()> {
+    let socket = mnl::Socket::new(mnl::Bus::Netfilter)?;
+    socket.send_all(batch)?;
+
+    let portid = socket.portid();
+    let mut buffer = vec![0; nftnl::nft_nlmsg_maxsize() as usize];
+    let seq = 2;
+    loop {
+        let len = socket.recv(&mut buffer[..])?;
+        if len == 0 {
+            return Ok(());
+        }
+        match mnl::cb_run(&buffer[..len], seq, portid)? {
+            mnl::CbResult::Stop => return Ok(()),
+            mnl::CbResult::Ok => (),
+        }
+    }
+}
+
+/// Sends `messages` and collects the objects `cb` parses from the replies until the kernel is
+/// done. That is the end of a dump, or the first acknowledgement of a batch. The kernel sends the
+/// echoes of a batch before the acknowledgements, so a batch must contain a message requesting an
+/// acknowledgement, or this blocks forever once the echoes are read.
+fn send_and_collect<'a, T>(
+    messages: impl IntoIterator<Item = &'a [u8]>,
+    cb: mnl::Callback<Vec<T>>,
+) -> io::Result<Vec<T>> {
+    let socket = mnl::Socket::new(mnl::Bus::Netfilter)?;
+    socket.send_all(messages)?;
+
+    let portid = socket.portid();
+    let mut buffer = vec![0; nftnl::nft_nlmsg_maxsize() as usize];
+    let mut objects = Vec::new();
+    loop {
+        let len = socket.recv(&mut buffer[..])?;
+        if len == 0 {
+            return Ok(objects);
+        }
+        match mnl::cb_run2(&buffer[..len], 0, portid, cb, &mut objects)? {
+            mnl::CbResult::Stop => return Ok(objects),
+            mnl::CbResult::Ok => (),
+        }
+    }
+}
+
+fn bind(addr: Ipv4Addr, port: u16) -> UdpSocket {
+    let socket = UdpSocket::bind(SocketAddr::from((addr, port))).unwrap();
+    socket.set_read_timeout(Some(RECV_TIMEOUT)).unwrap();
+    socket
+}
+
+/// Returns the source address of the next datagram received on `socket`, or `None` if nothing
+/// arrived within `RECV_TIMEOUT`.
+fn try_recv(socket: &UdpSocket) -> Option<SocketAddr> {
+    let mut buf = [0u8; 64];
+    match socket.recv_from(&mut buf) {
+        Ok((_, from)) => Some(from),
+        Err(ref e)
+            if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut =>
+        {
+            None
+        }
+        Err(e) => panic!("Unexpected error receiving: {}", e),
+    }
+}
+
+#[test]
+fn test_drop_by_source_ip() {
+    enter_netns();
+    let blocked = Ipv4Addr::new(127, 0, 0, 2);
+    let allowed = Ipv4Addr::new(127, 0, 0, 3);
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+    let mut rule = Rule::new(&chain);
+    rule.add_expr(&nft_expr!(payload ipv4 saddr));
+    rule.add_expr(&nft_expr!(cmp == blocked));
+    rule.add_expr(&nft_expr!(verdict drop));
+    batch.add(&rule, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let server = bind(Ipv4Addr::LOCALHOST, 5000);
+    bind(blocked, 0).send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert_eq!(try_recv(&server), None);
+    bind(allowed, 0).send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert_eq!(
+        try_recv(&server).map(|from| from.ip()),
+        Some(allowed.into())
+    );
+}
+
+#[test]
+fn test_dormant_table() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let mut table = table();
+    table.set_dormant(true);
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Drop);
+    batch.add(&chain, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let server = bind(Ipv4Addr::LOCALHOST, 5000);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    client.send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert!(try_recv(&server).is_some());
+
+    // Adding the table again without the flag attaches the chain and its drop policy.
+    let mut batch = Batch::new();
+    let awake = self::table();
+    assert!(!awake.is_dormant());
+    batch.add(&awake, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+    client.send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert_eq!(try_recv(&server), None);
+}
+
+#[test]
+fn test_reject_with_icmp_code() {
+    use nftnl::expr::{Icmpv4Code, RejectionType, Verdict};
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+    let mut rule = Rule::new(&chain);
+    add_udp_dport_match(&mut rule, 5000);
+    rule.add_expr(&Verdict::Reject(RejectionType::Icmpv4(
+        Icmpv4Code::HostUnreach,
+    )));
+    batch.add(&rule, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    // A connected UDP socket reports the ICMP error of the rejected datagram on the next call.
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    client.connect(("127.0.0.1", 5000)).unwrap();
+    client.send(b"x").unwrap();
+    let error = client.recv(&mut [0u8; 64]).unwrap_err();
+    assert_eq!(error.raw_os_error(), Some(libc::EHOSTUNREACH));
+}
+
+#[test]
+fn test_payload_to_second_register() {
+    use nftnl::{
+        expr::{Verdict, VmapLookup},
+        set::VerdictMap,
+    };
+    enter_netns();
+    let blocked = Ipv4Addr::new(127, 0, 0, 2);
+    let allowed = Ipv4Addr::new(127, 0, 0, 3);
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+    let mut map = VerdictMap::new(
+        &CString::new("blocked").unwrap(),
+        1,
+        &table,
+        ProtoFamily::Ipv4,
+    );
+    map.add_entry(&blocked, &Verdict::Drop);
+    batch.add(&map, MsgType::Add);
+    for elems in map.as_set().elems_iter() {
+        batch.add(&elems, MsgType::Add);
+    }
+
+    // The port match uses the first register, so the address goes into the second one.
+    let mut rule = Rule::new(&chain);
+    add_udp_dport_match(&mut rule, 5000);
+    rule.add_expr(&nft_expr!(payload ipv4 saddr => Register::Reg2));
+    rule.add_expr(&VmapLookup {
+        src_reg: Register::Reg2,
+        ..VmapLookup::new(&map)
+    });
+    batch.add(&rule, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let server = bind(Ipv4Addr::LOCALHOST, 5000);
+    bind(blocked, 0).send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert_eq!(try_recv(&server), None);
+    bind(allowed, 0).send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert_eq!(
+        try_recv(&server).map(|from| from.ip()),
+        Some(allowed.into())
+    );
+}
+
+#[test]
+fn test_accept_by_iface() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Drop);
+    batch.add(&chain, MsgType::Add);
+    let mut rule = Rule::new(&chain);
+    rule.add_expr(&nft_expr!(meta iifname));
+    rule.add_expr(&nft_expr!(cmp == "lo\0"));
+    rule.add_expr(&nft_expr!(verdict accept));
+    batch.add(&rule, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let server = bind(Ipv4Addr::LOCALHOST, 5000);
+    bind(Ipv4Addr::LOCALHOST, 0)
+        .send_to(b"x", ("127.0.0.1", 5000))
+        .unwrap();
+    assert!(try_recv(&server).is_some());
+}
+
+#[test]
+fn test_nat_loopback() {
+    enter_netns();
+    let target_port: u16 = 5001;
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let mut chain = Chain::new(&CString::new("output").unwrap(), &table);
+    chain.set_hook(Hook::Out, -100);
+    chain.set_type(ChainType::Nat);
+    batch.add(&chain, MsgType::Add);
+    let mut rule = Rule::new(&chain);
+    add_udp_dport_match(&mut rule, 5000);
+    rule.add_expr(&nftnl::expr::Immediate::new(
+        Ipv4Addr::LOCALHOST.octets(),
+        Register::Reg1,
+    ));
+    rule.add_expr(&nftnl::expr::Immediate::new(
+        target_port.to_be_bytes(),
+        Register::Reg2,
+    ));
+    rule.add_expr(&Nat {
+        nat_type: NatType::DNat,
+        family: ProtoFamily::Ipv4,
+        ip_register: Register::Reg1,
+        ip_register_max: None,
+        port_register: Some(Register::Reg2),
+        port_register_max: None,
+        flags: NatFlags::empty(),
+    });
+    batch.add(&rule, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let server = bind(Ipv4Addr::LOCALHOST, target_port);
+    bind(Ipv4Addr::LOCALHOST, 0)
+        .send_to(b"x", ("127.0.0.1", 5000))
+        .unwrap();
+    assert!(try_recv(&server).is_some());
+}
+
+#[test]
+fn test_conntrack_state() {
+    enter_netns();
+    let server_port: u16 = 5000;
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Drop);
+    batch.add(&chain, MsgType::Add);
+
+    let mut allow_established = Rule::new(&chain);
+    allow_established.add_expr(&nft_expr!(ct state));
+    let allowed_states = (States::ESTABLISHED | States::RELATED).bits();
+    allow_established.add_expr(&nft_expr!(bitwise mask allowed_states, xor 0u32));
+    allow_established.add_expr(&nft_expr!(cmp != 0u32));
+    allow_established.add_expr(&nft_expr!(verdict accept));
+    batch.add(&allow_established, MsgType::Add);
+
+    let mut allow_server = Rule::new(&chain);
+    add_udp_dport_match(&mut allow_server, server_port);
+    allow_server.add_expr(&nft_expr!(verdict accept));
+    batch.add(&allow_server, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let server = bind(Ipv4Addr::LOCALHOST, server_port);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    client.send_to(b"x", ("127.0.0.1", server_port)).unwrap();
+    let client_addr = try_recv(&server).expect("New connection to server was dropped");
+
+    // The reply belongs to an established connection and is accepted.
+    server.send_to(b"x", client_addr).unwrap();
+    assert_eq!(
+        try_recv(&client),
+        Some(SocketAddr::from((Ipv4Addr::LOCALHOST, server_port)))
+    );
+
+    // A new connection to the client port matches no rule and hits the drop policy.
+    bind(Ipv4Addr::LOCALHOST, 0)
+        .send_to(b"x", client_addr)
+        .unwrap();
+    assert_eq!(try_recv(&client), None);
+}
+
+#[test]
+fn test_meta_mark_set() {
+    enter_netns();
+    let mark: u32 = 0x42;
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "output", Hook::Out, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+
+    let mut set_mark = Rule::new(&chain);
+    add_udp_dport_match(&mut set_mark, 5000);
+    set_mark.add_expr(&nft_expr!(immediate u32 mark));
+    set_mark.add_expr(&nft_expr!(meta mark set));
+    batch.add(&set_mark, MsgType::Add);
+
+    let mut drop_marked = Rule::new(&chain);
+    drop_marked.add_expr(&nft_expr!(meta mark));
+    drop_marked.add_expr(&nft_expr!(cmp == mark));
+    drop_marked.add_expr(&nft_expr!(verdict drop));
+    batch.add(&drop_marked, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    // Only packets to port 5000 get the mark, and only marked packets are dropped.
+    let marked_server = bind(Ipv4Addr::LOCALHOST, 5000);
+    let unmarked_server = bind(Ipv4Addr::LOCALHOST, 5001);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    // The send itself fails with EPERM when an output rule drops the packet.
+    let _ = client.send_to(b"x", ("127.0.0.1", 5000));
+    assert_eq!(try_recv(&marked_server), None);
+    client.send_to(b"x", ("127.0.0.1", 5001)).unwrap();
+    assert!(try_recv(&unmarked_server).is_some());
+}
+
+#[test]
+fn test_rule_handles() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = Chain::new(&CString::new("regular").unwrap(), &table);
+    batch.add(&chain, MsgType::Add);
+    for _ in 0..2 {
+        let mut rule = Rule::new(&chain);
+        rule.add_expr(&nft_expr!(counter));
+        rule.set_echo(true);
+        batch.add(&rule, MsgType::Add);
+    }
+    let added: Vec<RuleInfo> = send_and_collect(&batch.finalize(), nftnl::get_rules_cb).unwrap();
+    assert_eq!(added.len(), 2);
+
+    let dumped = send_and_collect(
+        Some(&nftnl::get_rules_nlmsg(0, &chain)[..]),
+        nftnl::get_rules_cb,
+    )
+    .unwrap();
+    // Echoed rules carry no position, so only the handles can be compared with the dump.
+    assert_eq!(
+        dumped.iter().map(|r| r.handle).collect::<Vec<_>>(),
+        added.iter().map(|r| r.handle).collect::<Vec<_>>(),
+    );
+    assert_eq!(dumped[0].position, 0);
+    assert_eq!(dumped[1].position, dumped[0].handle);
+}
+
+#[test]
+fn test_rule_positions() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = Chain::new(&CString::new("regular").unwrap(), &table);
+    batch.add(&chain, MsgType::Add);
+    let mut middle = Rule::new(&chain);
+    middle.set_echo(true);
+    batch.add(&middle, MsgType::Add);
+    let middle = send_and_collect(&batch.finalize(), nftnl::get_rules_cb).unwrap();
+
+    let mut batch = Batch::new();
+    let mut before = Rule::new(&chain);
+    before.set_position_before_handle(middle[0].handle);
+    before.set_echo(true);
+    batch.add(&before, MsgType::Add);
+    let mut after = Rule::new(&chain);
+    after.set_position_after_handle(middle[0].handle);
+    after.set_echo(true);
+    batch.add(&after, MsgType::Add);
+    let mut first = Rule::new(&chain);
+    first.prepend();
+    first.set_echo(true);
+    batch.add(&first, MsgType::Add);
+    // Only the rule acknowledgements end the collection here, there is nothing else in the batch.
+    let added = send_and_collect(&batch.finalize(), nftnl::get_rules_cb).unwrap();
+    let handle = |index: usize| added[index].handle;
+
+    let rules = send_and_collect(
+        Some(&nftnl::get_rules_nlmsg(0, &chain)[..]),
+        nftnl::get_rules_cb,
+    )
+    .unwrap();
+    let order: Vec<_> = rules.iter().map(|rule| rule.handle).collect();
+    assert_eq!(order, [handle(2), handle(0), middle[0].handle, handle(1)]);
+}
+
+#[test]
+fn test_await_acks_of_rule_only_batch() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = Chain::new(&CString::new("regular").unwrap(), &table);
+    batch.add(&chain, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let socket = mnl::Socket::new(mnl::Bus::Netfilter).unwrap();
+    let mut batch = Batch::new();
+    batch.add(&Rule::new(&chain), MsgType::Add);
+    batch.finalize().send_and_await_acks(&socket).unwrap();
+
+    // Replacing a rule that does not exist is rejected, and must not be reported as success.
+    let mut batch = Batch::new();
+    let mut missing = Rule::new(&chain);
+    missing.set_handle(u64::from(u32::MAX));
+    batch.add(&missing, MsgType::Replace);
+    let err = batch.finalize().send_and_await_acks(&socket).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+
+    let rules = send_and_collect(
+        Some(&nftnl::get_rules_nlmsg(0, &chain)[..]),
+        nftnl::get_rules_cb,
+    )
+    .unwrap();
+    assert_eq!(rules.len(), 1);
+}
+
+#[test]
+fn test_replace_rule() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+    let mut rule = Rule::new(&chain);
+    add_udp_dport_match(&mut rule, 5000);
+    rule.add_expr(&nft_expr!(verdict drop));
+    rule.set_echo(true);
+    batch.add(&rule, MsgType::Add);
+    let added = send_and_collect(&batch.finalize(), nftnl::get_rules_cb).unwrap();
+
+    // Move the drop from port 5000 to 5001 without a moment of no rule.
+    let mut batch = Batch::new();
+    let mut replacement = Rule::new(&chain);
+    add_udp_dport_match(&mut replacement, 5001);
+    replacement.add_expr(&nft_expr!(verdict drop));
+    replacement.set_handle(added[0].handle);
+    batch.add(&replacement, MsgType::Replace);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let rules = send_and_collect(
+        Some(&nftnl::get_rules_nlmsg(0, &chain)[..]),
+        nftnl::get_rules_cb,
+    )
+    .unwrap();
+    assert_eq!(rules, added);
+
+    let first_server = bind(Ipv4Addr::LOCALHOST, 5000);
+    let second_server = bind(Ipv4Addr::LOCALHOST, 5001);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    client.send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    assert!(try_recv(&first_server).is_some());
+    client.send_to(b"x", ("127.0.0.1", 5001)).unwrap();
+    assert_eq!(try_recv(&second_server), None);
+}
+
+#[test]
+fn test_unsupported_msg_type_leaves_batch_intact() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    let replaced = panic::catch_unwind(AssertUnwindSafe(|| batch.add(&table, MsgType::Replace)));
+    assert!(replaced.is_err());
+    batch.add(&table, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+}
+
+#[test]
+fn test_meta_mark_set_from_second_register() {
+    enter_netns();
+    let mark: u32 = 0x42;
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "output", Hook::Out, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+
+    // The port match is done in the first register while the mark waits in the second.
+    let mut set_mark = Rule::new(&chain);
+    set_mark.add_expr(&nftnl::expr::Immediate::new(
+        mark.to_ne_bytes(),
+        Register::Reg2,
+    ));
+    add_udp_dport_match(&mut set_mark, 5000);
+    set_mark.add_expr(&nft_expr!(meta mark set from Register::Reg2));
+    batch.add(&set_mark, MsgType::Add);
+
+    let mut drop_marked = Rule::new(&chain);
+    drop_marked.add_expr(&nft_expr!(meta mark));
+    drop_marked.add_expr(&nft_expr!(cmp == mark));
+    drop_marked.add_expr(&nft_expr!(verdict drop));
+    batch.add(&drop_marked, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let marked_server = bind(Ipv4Addr::LOCALHOST, 5000);
+    let unmarked_server = bind(Ipv4Addr::LOCALHOST, 5001);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    // The send itself fails with EPERM when an output rule drops the packet.
+    let _ = client.send_to(b"x", ("127.0.0.1", 5000));
+    assert_eq!(try_recv(&marked_server), None);
+    client.send_to(b"x", ("127.0.0.1", 5001)).unwrap();
+    assert!(try_recv(&unmarked_server).is_some());
+}
+
+#[test]
+fn test_bitwise_between_registers() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let chain = base_chain(&table, "output", Hook::Out, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+
+    // Masks 0x142 from the second register into the third, leaving the first for the port.
+    let mut set_mark = Rule::new(&chain);
+    set_mark.add_expr(&nftnl::expr::Immediate::new(
+        0x142u32.to_ne_bytes(),
+        Register::Reg2,
+    ));
+    add_udp_dport_match(&mut set_mark, 5000);
+    set_mark.add_expr(&nft_expr!(
+        bitwise mask 0xffu32, xor 0u32, sreg Register::Reg2, dreg Register::Reg3
+    ));
+    set_mark.add_expr(&nft_expr!(meta mark set from Register::Reg3));
+    batch.add(&set_mark, MsgType::Add);
+
+    let mut drop_marked = Rule::new(&chain);
+    drop_marked.add_expr(&nft_expr!(meta mark));
+    drop_marked.add_expr(&nft_expr!(cmp == 0x42u32));
+    drop_marked.add_expr(&nft_expr!(verdict drop));
+    batch.add(&drop_marked, MsgType::Add);
+    send_and_process(&batch.finalize()).unwrap();
+
+    let marked_server = bind(Ipv4Addr::LOCALHOST, 5000);
+    let unmarked_server = bind(Ipv4Addr::LOCALHOST, 5001);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    let _ = client.send_to(b"x", ("127.0.0.1", 5000));
+    assert_eq!(try_recv(&marked_server), None);
+    client.send_to(b"x", ("127.0.0.1", 5001)).unwrap();
+    assert!(try_recv(&unmarked_server).is_some());
+}
+
+#[test]
+fn test_full_set_rejects_elements() {
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let mut set = DynamicSet::new(
+        &CString::new("limited").unwrap(),
+        0,
+        &table,
+        ProtoFamily::Ipv4,
+    );
+    set.as_set_mut().set_size(1);
+    set.add(&Ipv4Addr::new(10, 0, 0, 1));
+    set.add(&Ipv4Addr::new(10, 0, 0, 2));
+    batch.add(&set, MsgType::Add);
+    for elems in set.as_set().elems_iter() {
+        batch.add(&elems, MsgType::Add);
+    }
+
+    let error = send_and_process(&batch.finalize()).unwrap_err();
+    assert_eq!(error.raw_os_error(), Some(libc::ENFILE));
+}
+
+#[cfg(feature = "nftnl-1-0-8")]
+#[test]
+fn test_named_limit_is_shared() {
+    use nftnl::{
+        expr::{LimitPer, LimitRef, LimitUnit},
+        object::NamedLimit,
+    };
+    enter_netns();
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let mut limit = NamedLimit::new(
+        &CString::new("shared").unwrap(),
+        &table,
+        1,
+        LimitUnit::Packets,
+        LimitPer::Minute,
+    );
+    limit.inverse = true;
+    batch.add(&limit, MsgType::Add);
+    let chain = base_chain(&table, "input", Hook::In, Policy::Accept);
+    batch.add(&chain, MsgType::Add);
+    for port in [5000, 5001] {
+        let mut rule = Rule::new(&chain);
+        add_udp_dport_match(&mut rule, port);
+        rule.add_expr(&LimitRef::new(&limit));
+        rule.add_expr(&nft_expr!(verdict drop));
+        batch.add(&rule, MsgType::Add);
+    }
+    send_and_process(&batch.finalize()).unwrap();
+
+    // Use up the whole burst through the first rule. The second rule then sees the same empty
+    // bucket and drops the first packet it gets.
+    let first_server = bind(Ipv4Addr::LOCALHOST, 5000);
+    let second_server = bind(Ipv4Addr::LOCALHOST, 5001);
+    let client = bind(Ipv4Addr::LOCALHOST, 0);
+    for _ in 0..10 {
+        client.send_to(b"x", ("127.0.0.1", 5000)).unwrap();
+    }
+    assert!(try_recv(&first_server).is_some());
+    client.send_to(b"x", ("127.0.0.1", 5001)).unwrap();
+    assert_eq!(try_recv(&second_server), None);
+}
+
+#[test]
+fn test_list_set_elements() {
+    use nftnl::set::{get_set_elements_cb, get_set_elements_nlmsg, get_sets_cb, get_sets_nlmsg};
+    enter_netns();
+    let elements = [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)];
+
+    let mut batch = Batch::new();
+    let table = table();
+    batch.add(&table, MsgType::Add);
+    let mut set = DynamicSet::new(
+        &CString::new("listed").unwrap(),
+        0,
+        &table,
+        ProtoFamily::Ipv4,
+    );
+    for element in &elements {
+        set.add(element);
+    }
+    batch.add(&set, MsgType::Add);
+    for elems in set.as_set().elems_iter() {
+        batch.add(&elems, MsgType::Add);
+    }
+    send_and_process(&batch.finalize()).unwrap();
+
+    let sets = send_and_collect(Some(&get_sets_nlmsg(0, &table)[..]), get_sets_cb).unwrap();
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].name.to_str(), Ok("listed"));
+    assert_eq!(sets[0].key_len, 4);
+
+    let mut keys = send_and_collect(
+        Some(&get_set_elements_nlmsg(0, set.as_set())[..]),
+        get_set_elements_cb,
+    )
+    .unwrap();
+    keys.sort();
+    let expected: Vec<Box<[u8]>> = elements.iter().map(|addr| addr.octets().into()).collect();
+    assert_eq!(keys, expected);
+}